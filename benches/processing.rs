@@ -0,0 +1,118 @@
+use auto_header::{config::Config, header::FileDates, process_file, resolve, Options};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::{env, fs, hint::black_box, path::PathBuf, process};
+
+/// Number of files of the benchmarked tree.
+const FILES: usize = 200;
+
+/// Configuration of the benchmark, the project root being appended to it.
+const CONFIG: &str = r##"
+create = true
+update = true
+language_strict = true
+
+[data]
+author = "Jane Doe"
+author_mail = "jane@doe.org"
+cp_holders = "Jane Doe"
+copyright_notice = "Copyright © #cp_year #cp_holders - All rights reserved"
+
+[default]
+name = "*"
+prefix = "# "
+before = []
+after = [""]
+template = """
+File: #file_relative_path
+Project: #project_name
+Creation date: #file_creation
+Author: #author_name #author_mail
+-----
+Last modified: #date_now
+Modified by: #author_name
+-----
+#copyright_notice"""
+track_changes = ["File: ", "Last modified: ", "Modified by: ", "Copyright "]
+
+[[language]]
+name = "python"
+before = ["#!/usr/bin/env python3", ""]
+
+[[language]]
+name = "rust"
+prefix = "// "
+
+[[language]]
+name = "c"
+prefix = " * "
+block_start = "/*"
+block_end = " */"
+"##;
+
+/// Builds a realistic tree of source files (without headers), spread over a
+/// few directories and languages.
+fn build_tree(root: &PathBuf) -> Vec<String> {
+    let _ = fs::remove_dir_all(root);
+    (0..FILES)
+        .map(|i| {
+            let dir = root.join(format!("module_{}", i % 10));
+            fs::create_dir_all(&dir).unwrap();
+            let extension = ["rs", "py", "c"][i % 3];
+            let path = dir.join(format!("file_{}.{}", i, extension));
+            let body = "let value = compute(42);\n".repeat(100);
+            fs::write(&path, body).unwrap();
+            path.to_string_lossy().into_owned()
+        })
+        .collect()
+}
+
+/// Processes every file of the tree, as a run over the whole project would.
+fn process_tree(config: &Config, files: &[String], options: &Options) {
+    for file in files {
+        let (project, template) = resolve(config, file).unwrap();
+        black_box(process_file(file, &project, &template, options).unwrap());
+    }
+}
+
+fn processing(c: &mut Criterion) {
+    let root = env::temp_dir().join(format!("auto-header-bench-{}", process::id()));
+    let config = format!("{}\n[[project]]\nroot = {:?}\n", CONFIG, root);
+    let config = Config::parse(&config).unwrap();
+    let options = Options {
+        create: true,
+        update: true,
+        ..Default::default()
+    };
+
+    c.bench_function("create headers", |b| {
+        b.iter_batched(
+            || build_tree(&root),
+            |files| process_tree(&config, &files, &options),
+            BatchSize::PerIteration,
+        )
+    });
+
+    let files = build_tree(&root);
+    process_tree(&config, &files, &options);
+    c.bench_function("check up-to-date headers", |b| {
+        b.iter(|| process_tree(&config, &files, &options))
+    });
+
+    let (project, template) = resolve(&config, &files[0]).unwrap();
+    let dates = FileDates::from_file(&files[0]).unwrap();
+    c.bench_function("fill template", |b| {
+        b.iter(|| {
+            auto_header::header::fill_template(
+                &template,
+                &project,
+                &files[0],
+                &project.root,
+                black_box(&dates),
+            )
+        })
+    });
+    let _ = fs::remove_dir_all(&root);
+}
+
+criterion_group!(benches, processing);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+use regex::Regex;
+use std::{fs, path::Path};
+
+/// Locations of the `CODEOWNERS` file in a repository, in the order they’re
+/// looked up (GitHub and GitLab conventions).
+const LOCATIONS: [&str; 4] = [
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+/// Gets the owners of a file declared in the `CODEOWNERS` file of its project.
+/// As on GitHub and GitLab, the last rule matching the file takes precedence.
+/// Nothing is returned if there’s no `CODEOWNERS` file, or if no rule with
+/// owners matches the file.
+///
+/// # Arguments
+/// * `root` - Root of the project.
+/// * `path` - Path of the file, relative to the root (with forward slashes).
+///
+/// # Example
+/// ```ignore
+/// if let Some(owners) = file_owners(&project.root, "src/main.rs") {
+///     println!("owned by {}", owners.join(", "));
+/// }
+/// ```
+pub fn file_owners(root: &str, path: &str) -> Option<Vec<String>> {
+    let content = LOCATIONS
+        .iter()
+        .find_map(|location| fs::read_to_string(Path::new(root).join(location)).ok())?;
+    content
+        .lines()
+        .rev()
+        .map(str::trim)
+        // GitLab sections (`[Documentation]`) only group the rules.
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+        .find_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next()?;
+            let owners: Vec<String> = fields
+                .take_while(|field| !field.starts_with('#'))
+                .map(str::to_owned)
+                .collect();
+            pattern_regex(pattern)
+                .is_some_and(|regex| regex.is_match(path))
+                .then_some(owners)
+        })
+        .filter(|owners| !owners.is_empty())
+}
+
+/// Converts a `CODEOWNERS` pattern (`.gitignore` syntax) into a regular
+/// expression matching the paths relative to the root of the repository.
+///
+/// # Arguments
+/// * `pattern` - Pattern of a rule.
+fn pattern_regex(pattern: &str) -> Option<Regex> {
+    // Patterns holding a slash (but a final one) are relative to the root.
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let directory = pattern.ends_with('/');
+    let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    // A pattern matching a directory matches everything it holds, but a final
+    // `*` only matches the files of its directory (`docs/*`).
+    regex.push_str(if directory {
+        "/.*$"
+    } else if pattern.ends_with('*') && !pattern.ends_with("**") {
+        "$"
+    } else {
+        "(?:/.*)?$"
+    });
+    Regex::new(&regex).ok()
+}
@@ -0,0 +1,1792 @@
+use detect_lang::from_path;
+use serde::{Deserialize, Deserializer};
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    error::{AutoHeaderError, Result},
+    header::template_tokens,
+    licenses::get_license,
+    locales::is_supported_locale,
+    paths::{absolute_path, default_config_path, expand_path},
+};
+
+/// Global configuration for the auto-header.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    /// Controls wether or not a header should be created if absent.
+    #[serde(default)]
+    pub create: bool,
+    /// Controls wether or not an existing header should be updated.
+    #[serde(default)]
+    pub update: bool,
+    /// Don’t create headers in files that are empty or hold only whitespace,
+    /// until they have actual content.
+    #[serde(default)]
+    pub skip_empty_files: bool,
+    /// Take the dates of the files from their git history when they’re
+    /// tracked: the creation date from the first commit changing them, the
+    /// modification date from the last one (if they didn’t change since), and
+    /// the author, when `data` sets none, from the first commit.
+    #[serde(default)]
+    pub use_git: bool,
+    /// Determines if we should use the default template for any language
+    /// with no specific template defined.
+    #[serde(default)]
+    pub language_strict: bool,
+    /// Default locale to use for date formatting
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// Data used to fill the templates (names, mail addresses, *etc.*).
+    pub data: ConfigData,
+    /// File of key-values completing `data` (a TOML table of strings, or an
+    /// `.env` file of `KEY=value` lines), relative to the configuration file.
+    /// The keys named after a field of `data` (`author_mail`, …) fill it, the
+    /// other ones are custom tokens. The values set in `data` take precedence.
+    pub data_file: Option<String>,
+    /// Default template (fall back if no language specific one exists).
+    /// It will also serve to fill in the blanks left in language specific
+    /// templates.
+    pub default: Template,
+    /// Language specific templates.
+    pub language: Option<Vec<Template>>,
+    /// Projects configurations.
+    pub project: Option<Vec<Project>>,
+    /// File or directory (`Cargo.toml`, `.git`, …) marking the root of a project.
+    /// The closest directory containing it is used as the project’s root, unless
+    /// a project is explicitly configured closer to the file. Settings are then
+    /// inherited from the enclosing configured project, if any.
+    pub root_marker: Option<String>,
+    /// What to do when several projects have the same root (the first one is
+    /// used): warn (default), or refuse the configuration.
+    #[serde(default)]
+    pub duplicate_projects: DuplicateProjects,
+    /// Languages of the files, by extension (`h = "cpp"`) or by file name
+    /// (`Jenkinsfile = "groovy"`), taking precedence over the detection.
+    pub extensions: Option<BTreeMap<String, String>>,
+    /// When checking the headers (`--check`), number of days the modification
+    /// date written in a header can lag behind the file’s before the header is
+    /// flagged as stale. Within that age, a header whose only difference is its
+    /// modification date is accepted.
+    pub max_age_days: Option<u32>,
+    /// Shell command run once at the end of a run modifying the files (not
+    /// with `--dry-run`, `--diff`, `--check`, or on the standard input). It receives the number of
+    /// files by outcome as JSON on its standard input, and in the
+    /// `AUTO_HEADER_<OUTCOME>` environment variables (`AUTO_HEADER_CREATED`, …).
+    pub on_complete: Option<String>,
+}
+
+/// Name of the configuration file shared in a repository, found above the
+/// processed files.
+pub const SHARED_CONFIG: &str = "auto-header.toml";
+
+/// Gets the path of the configuration file loaded for a target: the given one
+/// (`~` and variables expanded), or else the closest `auto-header.toml` file
+/// above the target, or failing that the user’s configuration file.
+///
+/// # Arguments
+/// * `path` - Path of the configuration file, if one is given.
+/// * `target` - File or directory the configuration is loaded for.
+///
+/// # Example
+/// ```
+/// use auto_header::config::config_path;
+/// use std::path::Path;
+///
+/// let path = config_path(Some(Path::new("/etc/auto-header.toml")), Path::new("./src/main.rs"))?;
+/// assert_eq!(path, "/etc/auto-header.toml");
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn config_path(path: Option<&Path>, target: &Path) -> Result<String> {
+    Ok(match path {
+        Some(path) => expand_path(&path.to_string_lossy()),
+        None => absolute_path(target)?
+            .ancestors()
+            .map(|dir| dir.join(SHARED_CONFIG))
+            .find(|path| path.is_file())
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|| expand_path(&default_config_path())),
+    })
+}
+
+impl Config {
+    /// Loads the configuration file, with every check done on it: its format,
+    /// its default template’s completeness, its projects’ roots (expanded),
+    /// and the templates included by its templates. Errors name the file.
+    ///
+    /// Without an explicit path, the closest `auto-header.toml` file above the
+    /// target is used (a configuration shared in a repository), or failing that
+    /// the user’s configuration file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the configuration file (`~` and variables are expanded).
+    /// * `target` - File or directory the configuration is loaded for.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let config = Config::load(None, Path::new("./src/main.rs"))?;
+    /// let (project, template) = resolve(&config, "./src/main.rs")?;
+    /// ```
+    pub fn load(path: Option<&Path>, target: &Path) -> Result<Self> {
+        let path = config_path(path, target)?;
+        if !Path::new(&path).is_file() {
+            return Err(AutoHeaderError::ConfigNotFound(path));
+        }
+        let extension = Path::new(&path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        if extension.is_some_and(|extension| extension != "toml") {
+            return Err(AutoHeaderError::ConfigFormat(path));
+        }
+
+        let content = fs::read_to_string(&path).map_err(AutoHeaderError::read(&path))?;
+        let dir = Path::new(&path).parent().unwrap_or(Path::new("."));
+        Self::load_content(&content, &path, dir)
+    }
+
+    /// Loads a configuration given inline (on the command line), with the same
+    /// checks as `load`. Its `data_file` is relative to the current directory.
+    ///
+    /// # Arguments
+    /// * `content` - Content of the configuration (TOML).
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::Config;
+    ///
+    /// let config = Config::load_inline(
+    ///     r##"
+    ///     [data]
+    ///     [default]
+    ///     name = "*"
+    ///     prefix = "# "
+    ///     before = []
+    ///     after = [""]
+    ///     template = "File: #file_relative_path"
+    ///     track_changes = ["File: "]
+    ///     "##,
+    /// )?;
+    /// assert_eq!(config.default.prefix.as_deref(), Some("# "));
+    /// assert!(Config::load_inline("[default]\nname = \"*\"").is_err());
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn load_inline(content: &str) -> Result<Self> {
+        Self::load_content(content, "(inline configuration)", Path::new("."))
+    }
+
+    /// Parses and checks the content of a configuration.
+    ///
+    /// # Arguments
+    /// * `content` - Content of the configuration.
+    /// * `origin` - Where the configuration comes from, named in the errors.
+    /// * `dir` - Directory the configuration’s relative paths are relative to.
+    fn load_content(content: &str, origin: &str, dir: &Path) -> Result<Self> {
+        let config = Self::parse(content).map_err(|err| match err {
+            AutoHeaderError::ConfigParse(err) => AutoHeaderError::InvalidConfig {
+                path: origin.to_owned(),
+                reason: err.to_string(),
+            },
+            err => err,
+        })?;
+        let config = match config.data_file.clone() {
+            Some(file) => config.with_data_file(&dir.join(expand_path(&file)))?,
+            None => config,
+        };
+        for template in config.language.iter().flatten() {
+            config.with_includes(template.clone().merge(&config.default))?;
+        }
+        config.with_includes(config.default.clone().merge(&config.default))?;
+        Ok(config)
+    }
+
+    /// Parses a configuration, expanding `~` and environment variables in the
+    /// projects’ roots.
+    ///
+    /// # Arguments
+    /// * `content` - Content of the configuration file.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::Config;
+    ///
+    /// let config = Config::parse(
+    ///     r##"
+    ///     [data]
+    ///     [default]
+    ///     name = "*"
+    ///     prefix = "# "
+    ///     before = []
+    ///     after = [""]
+    ///     template = "File: #file_relative_path"
+    ///     track_changes = ["File: "]
+    ///     [[project]]
+    ///     root = "/home/user/project"
+    ///     "##,
+    /// )?;
+    /// assert_eq!(config.project.unwrap()[0].root, "/home/user/project");
+    /// assert!(Config::parse("[default").is_err());
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config: Config = toml::from_str(content)?;
+        config.check_default()?;
+        config.check_data()?;
+        config
+            .project
+            .iter_mut()
+            .flatten()
+            .for_each(|project| project.root = expand_path(&project.root));
+        if config.duplicate_projects == DuplicateProjects::Error {
+            if let Some(root) = config.duplicate_roots().into_iter().next() {
+                return Err(AutoHeaderError::DuplicateProject(root));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Gets the roots shared by several projects (only the first of them
+    /// applies to the files).
+    ///
+    /// # Example
+    /// ```
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// assert!(config.duplicate_roots().is_empty());
+    /// let mut config = config;
+    /// let projects = config.project.as_mut().unwrap();
+    /// projects.push(projects[0].clone());
+    /// assert_eq!(config.duplicate_roots(), ["/home/user/project"]);
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn duplicate_roots(&self) -> Vec<String> {
+        let projects = self.project.as_deref().unwrap_or_default();
+        let mut roots: Vec<String> = Vec::new();
+        for (index, project) in projects.iter().enumerate() {
+            let root = Path::new(&project.root);
+            if projects[..index].iter().any(|p| Path::new(&p.root) == root)
+                && !roots.iter().any(|r| Path::new(r) == root)
+            {
+                roots.push(project.root.clone());
+            }
+        }
+        roots
+    }
+
+    /// Adds the text of the templates included by a merged template (`include`)
+    /// after its own, recursively.
+    ///
+    /// Fails if an included template doesn’t exist, or if templates include
+    /// each other.
+    ///
+    /// # Arguments
+    /// * `template` - Merged template.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::{config::Template, error::AutoHeaderError};
+    ///
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// let template = Template {
+    ///     include: Some(vec![String::from("rust")]),
+    ///     ..config.default.clone()
+    /// };
+    /// let mut config = config;
+    /// config.language.as_mut().unwrap()[0].template = Some(String::from("SPDX-License-Identifier: MIT"));
+    /// let template = config.with_includes(template)?;
+    /// assert_eq!(
+    ///     template.template.as_deref(),
+    ///     Some("File: #file_relative_path\nAuthor: #author\nSPDX-License-Identifier: MIT")
+    /// );
+    ///
+    /// config.language.as_mut().unwrap()[0].include = Some(vec![String::from("rust")]);
+    /// let template = Template { include: Some(vec![String::from("rust")]), ..config.default.clone() };
+    /// assert!(matches!(config.with_includes(template), Err(AutoHeaderError::IncludeCycle(_))));
+    /// # Ok::<(), AutoHeaderError>(())
+    /// ```
+    pub fn with_includes(&self, mut template: Template) -> Result<Template> {
+        let names = template.include.clone().unwrap_or_default();
+        let included = self.included_texts(&names, &mut Vec::new())?;
+        if !included.is_empty() {
+            let text = template.template.into_iter().chain(included);
+            template.template = Some(text.collect::<Vec<_>>().join("\n"));
+        }
+        Ok(template)
+    }
+
+    /// Gets the texts of included templates, followed by the ones they include.
+    ///
+    /// # Arguments
+    /// * `names` - Names of the included templates.
+    /// * `chain` - Names of the templates being included, to detect cycles.
+    fn included_texts(&self, names: &[String], chain: &mut Vec<String>) -> Result<Vec<String>> {
+        let mut texts = Vec::new();
+        for name in names {
+            if chain.contains(name) {
+                chain.push(name.clone());
+                return Err(AutoHeaderError::IncludeCycle(chain.join(" -> ")));
+            }
+            let included = get_named_template(self, name)
+                .ok_or_else(|| AutoHeaderError::NoTemplate(name.clone()))?;
+            chain.push(name.clone());
+            texts.extend(included.template);
+            texts.extend(self.included_texts(&included.include.unwrap_or_default(), chain)?);
+            chain.pop();
+        }
+        Ok(texts)
+    }
+
+    /// Checks that every `#token` of the templates can be filled with the data
+    /// of the configuration, globally and for every project. Gets a
+    /// description of every token that can’t be (empty if all of them can).
+    ///
+    /// Tokens defined on the command line (`--define`) or in the
+    /// `.auto-header.toml` files can’t be known here, and are reported too.
+    ///
+    /// # Example
+    /// ```
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// assert!(config.unresolved_tokens().is_empty());
+    /// let mut config = config;
+    /// config.default.template = Some(String::from("File: #file_relative_path\nTeam: #team"));
+    /// let problems = config.unresolved_tokens();
+    /// assert_eq!(
+    ///     problems[0],
+    ///     "Template * (global data): #team requires data.tokens.team, which isn’t set."
+    /// );
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn unresolved_tokens(&self) -> Vec<String> {
+        let scopes = std::iter::once((String::from("global data"), None)).chain(
+            self.project.iter().flatten().map(|project| {
+                let name = project.name.clone().unwrap_or(project.root.clone());
+                (format!("project {}", name), Some(project))
+            }),
+        );
+        let templates: Vec<&Template> = std::iter::once(&self.default)
+            .chain(self.language.iter().flatten())
+            .collect();
+
+        let mut problems = Vec::new();
+        for (scope, project) in scopes {
+            let data = project.and_then(|project| project.data.as_ref());
+            // Value of a data field for the scope, the project’s taking precedence.
+            let field = |get: fn(&ConfigData) -> Option<&String>| {
+                data.and_then(get).or(get(&self.data)).cloned()
+            };
+            let notice = project
+                .and_then(|project| project.copyright_notice.clone())
+                .or(field(|data| data.copyright_notice.as_ref()));
+            for template in &templates {
+                let Some(text) = template
+                    .template
+                    .as_ref()
+                    .or(self.default.template.as_ref())
+                else {
+                    continue;
+                };
+                let mut tokens = template_tokens(text);
+                if let Some(notice) = notice
+                    .as_ref()
+                    .filter(|_| tokens.iter().any(|t| t == "copyright_notice"))
+                {
+                    tokens.extend(template_tokens(get_license(notice)));
+                }
+                if tokens.iter().any(|t| t == "author") {
+                    match field(|data| data.author_format.as_ref()) {
+                        Some(format) => tokens.extend(template_tokens(&format)),
+                        None => tokens.push(String::from("author_name")),
+                    }
+                }
+
+                let names = if template.name.is_empty() {
+                    String::from("default")
+                } else {
+                    template.name.join(", ")
+                };
+                for token in tokens {
+                    let missing = match token.as_str() {
+                        "author" | "authors" | "file_creation" | "date_now"
+                        | "file_modified_iso" | "file_relative_path" | "project_name"
+                        | "signature" | "cp_year" | "copyright_symbol" | "last_author"
+                        | "last_author_mail" | "contributors" | "tz_offset" => None,
+                        "author_name" => field(|data| data.author.as_ref())
+                            .is_none()
+                            .then(|| String::from("data.author")),
+                        "author_mail" => field(|data| data.author_mail.as_ref())
+                            .is_none()
+                            .then(|| String::from("data.author_mail")),
+                        "cp_holders" => field(|data| data.cp_holders.as_ref())
+                            .is_none()
+                            .then(|| String::from("data.cp_holders")),
+                        "copyright_notice" => notice
+                            .is_none()
+                            .then(|| String::from("data.copyright_notice")),
+                        custom => (!data
+                            .and_then(|data| data.tokens.as_ref())
+                            .into_iter()
+                            .chain(&self.data.tokens)
+                            .any(|tokens| tokens.contains_key(custom)))
+                        .then(|| format!("data.tokens.{}", custom)),
+                    };
+                    if let Some(missing) = missing {
+                        let problem = format!(
+                            "Template {} ({}): #{} requires {}, which isn’t set.",
+                            names, scope, token, missing
+                        );
+                        if !problems.contains(&problem) {
+                            problems.push(problem);
+                        }
+                    }
+                }
+            }
+        }
+        problems
+    }
+
+    /// Checks that the default template holds every field the language
+    /// specific templates may need to be completed with.
+    ///
+    /// # Example
+    /// ```
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// config.check_default()?;
+    /// let mut config = config;
+    /// config.default.track_changes = None;
+    /// assert_eq!(
+    ///     config.check_default().unwrap_err().to_string(),
+    ///     "Missing field default.track_changes in the configuration."
+    /// );
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn check_default(&self) -> Result<()> {
+        self.default.is_complete().map_err(|missing| {
+            let fields: Vec<String> = missing
+                .iter()
+                .map(|field| format!("default.{}", field))
+                .collect();
+            AutoHeaderError::MissingField(fields.join(", "))
+        })
+    }
+
+    /// Checks that the copyright notice the templates write is set, in the
+    /// data or else in every project.
+    ///
+    /// # Example
+    /// ```
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// config.check_data()?;
+    /// let mut config = config;
+    /// config.default.template = Some(String::from("#copyright_notice"));
+    /// assert!(config.check_data().is_err());
+    /// config.data.copyright_notice = Some(String::from("MIT"));
+    /// config.check_data()?;
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn check_data(&self) -> Result<()> {
+        let projects = self.project.as_deref().unwrap_or_default();
+        let set_by_projects = !projects.is_empty()
+            && projects.iter().all(|project| {
+                project.copyright_notice.is_some()
+                    || project
+                        .data
+                        .as_ref()
+                        .is_some_and(|data| data.copyright_notice.is_some())
+            });
+        if set_by_projects {
+            return Ok(());
+        }
+        [&self.default]
+            .into_iter()
+            .chain(self.language.iter().flatten())
+            .try_for_each(|template| self.data.check(template))
+    }
+
+    /// Completes the configuration’s data with the key-values of a data file
+    /// (`data_file`), the values already set taking precedence.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the data file: a TOML table of strings if its
+    ///   extension is `.toml`, lines of `KEY=value` otherwise.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let config = Config::parse(&fs::read_to_string(args.config)?)?
+    ///     .with_data_file(Path::new("~/.config/auto-header/identity.env"))?;
+    /// ```
+    pub fn with_data_file(mut self, path: &Path) -> Result<Self> {
+        let name = path.to_string_lossy().into_owned();
+        if !path.is_file() {
+            return Err(AutoHeaderError::FileNotFound(name));
+        }
+        let content = fs::read_to_string(path).map_err(AutoHeaderError::read(&name))?;
+        let invalid = |reason: String| AutoHeaderError::InvalidConfig {
+            path: name.clone(),
+            reason,
+        };
+        let values: BTreeMap<String, String> = if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            toml::from_str(&content).map_err(|err| invalid(err.to_string()))?
+        } else {
+            parse_env(&content).map_err(invalid)?
+        };
+        self.data.fill(values);
+        Ok(self)
+    }
+
+    /// Adds the mappings of a shared language map to the configuration’s
+    /// `extensions`, those of the configuration taking precedence.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the language map (a TOML table of `extension = "language"`).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let config = Config::parse(&fs::read_to_string(args.config)?)?
+    ///     .with_language_map("~/.config/auto-header/languages.toml")?;
+    /// ```
+    pub fn with_language_map(mut self, path: &str) -> Result<Self> {
+        let path = expand_path(path);
+        if !Path::new(&path).exists() {
+            return Err(AutoHeaderError::FileNotFound(path));
+        }
+        let content = fs::read_to_string(&path).map_err(AutoHeaderError::read(&path))?;
+        let mut extensions: BTreeMap<String, String> = toml::from_str(&content)?;
+        extensions.extend(self.extensions.unwrap_or_default());
+        self.extensions = Some(extensions);
+        Ok(self)
+    }
+}
+
+/// Parses the key-values of an `.env` file: `KEY=value` lines (`export` and
+/// quotes around the value allowed), blank lines and `#` comments being
+/// ignored. Keys are lowercased to match the tokens.
+///
+/// # Arguments
+/// * `content` - Content of the file.
+fn parse_env(content: &str) -> std::result::Result<BTreeMap<String, String>, String> {
+    let mut values = BTreeMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected KEY=value", index + 1))?;
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| {
+                value
+                    .strip_prefix(*quote)
+                    .and_then(|value| value.strip_suffix(*quote))
+            })
+            .unwrap_or(value);
+        values.insert(key.trim().to_lowercase(), value.to_owned());
+    }
+    Ok(values)
+}
+
+pub fn default_locale() -> String {
+    String::from("en")
+}
+
+/// Data used to fill the templates.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ConfigData {
+    /// Name of the author.
+    pub author: Option<String>,
+    /// Mail address of the author.
+    pub author_mail: Option<String>,
+    /// How `#author` renders the main author, from the `#author_name` and
+    /// `#author_mail` tokens (`#author_mail (#author_name)` for example).
+    pub author_format: Option<String>,
+    /// Copyright holders if any.
+    pub cp_holders: Option<String>,
+    /// Copyright notice.
+    pub copyright_notice: Option<String>,
+    /// Copyright symbol replacing `#copyright_symbol` (`©` by default).
+    pub copyright_symbol: Option<String>,
+    /// Render the copyright symbol in plain ASCII (`(c)`) when it isn’t, for
+    /// files that can’t hold `©`.
+    pub ascii_symbols: Option<bool>,
+    /// Other authors, rendered along with the main one by `#authors`.
+    pub authors: Option<Vec<String>>,
+    /// Sort the authors alphabetically when rendering `#authors`.
+    pub authors_sort: Option<bool>,
+    /// Remove duplicated authors when rendering `#authors`.
+    pub authors_dedupe: Option<bool>,
+    /// Maximum number of authors rendered by `#contributors` (the most active
+    /// ones are kept).
+    pub max_contributors: Option<usize>,
+    /// Custom tokens: each `key = "value"` entry replaces `#key` in the templates.
+    pub tokens: Option<BTreeMap<String, String>>,
+    /// Take the main author (`#author_name`, `#author`) from the owners of the
+    /// file declared in the project’s `CODEOWNERS` file, when a rule matches it.
+    pub author_from_codeowners: Option<bool>,
+}
+
+impl ConfigData {
+    /// Checks that the data hold the values a template writes, that can’t be
+    /// left empty: the copyright notice, when the template holds
+    /// `#copyright_notice`.
+    ///
+    /// # Arguments
+    /// * `template` - Template the data fill (merged).
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::{ConfigData, Template};
+    ///
+    /// let template = Template {
+    ///     template: Some(String::from("#copyright_notice")),
+    ///     ..Default::default()
+    /// };
+    /// assert!(ConfigData::default().check(&template).is_err());
+    /// let data = ConfigData {
+    ///     copyright_notice: Some(String::from("MIT")),
+    ///     ..Default::default()
+    /// };
+    /// data.check(&template)?;
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn check(&self, template: &Template) -> Result<()> {
+        let text = template.template.as_deref().unwrap_or_default();
+        if text.contains("#copyright_notice") && self.copyright_notice.is_none() {
+            return Err(AutoHeaderError::MissingField(String::from(
+                "data.copyright_notice",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fills the unset values with key-values (from a data file): the keys
+    /// named after a field set it, the other ones are custom tokens.
+    ///
+    /// # Arguments
+    /// * `values` - Key-values filling the data.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::ConfigData;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut data = ConfigData {
+    ///     author: Some(String::from("Jane Doe")),
+    ///     ..Default::default()
+    /// };
+    /// data.fill(BTreeMap::from([
+    ///     (String::from("author"), String::from("John Smith")),
+    ///     (String::from("author_mail"), String::from("jane@doe.org")),
+    ///     (String::from("team"), String::from("Core")),
+    /// ]));
+    /// assert_eq!(data.author.as_deref(), Some("Jane Doe"));
+    /// assert_eq!(data.author_mail.as_deref(), Some("jane@doe.org"));
+    /// assert_eq!(data.tokens.unwrap()["team"], "Core");
+    /// ```
+    pub fn fill(&mut self, values: BTreeMap<String, String>) {
+        for (key, value) in values {
+            let field = match key.as_str() {
+                "author" => &mut self.author,
+                "author_mail" => &mut self.author_mail,
+                "author_format" => &mut self.author_format,
+                "cp_holders" => &mut self.cp_holders,
+                "copyright_notice" => &mut self.copyright_notice,
+                "copyright_symbol" => &mut self.copyright_symbol,
+                _ => {
+                    self.tokens
+                        .get_or_insert_with(BTreeMap::new)
+                        .entry(key)
+                        .or_insert(value);
+                    continue;
+                }
+            };
+            field.get_or_insert(value);
+        }
+    }
+
+    /// Merge a given `ConfigData` with the default one.
+    ///
+    /// # Arguments
+    /// * `default` - `ConfigData` by default, used to fill
+    ///   unspecified values.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::ConfigData;
+    ///
+    /// let project = ConfigData {
+    ///     author: Some(String::from("John Smith")),
+    ///     ..Default::default()
+    /// };
+    /// let data = project.merge(&ConfigData {
+    ///     author: Some(String::from("Jane Doe")),
+    ///     author_mail: Some(String::from("jane@doe.org")),
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(data.author.as_deref(), Some("John Smith"));
+    /// assert_eq!(data.author_mail.as_deref(), Some("jane@doe.org"));
+    /// ```
+    pub fn merge(self, default: &ConfigData) -> Self {
+        Self {
+            author: self.author.or(default.author.clone()),
+            author_mail: self.author_mail.or(default.author_mail.clone()),
+            author_format: self.author_format.or(default.author_format.clone()),
+            cp_holders: self.cp_holders.or(default.cp_holders.clone()),
+            copyright_notice: self.copyright_notice.or(default.copyright_notice.clone()),
+            copyright_symbol: self.copyright_symbol.or(default.copyright_symbol.clone()),
+            ascii_symbols: self.ascii_symbols.or(default.ascii_symbols),
+            authors: self.authors.or(default.authors.clone()),
+            authors_sort: self.authors_sort.or(default.authors_sort),
+            authors_dedupe: self.authors_dedupe.or(default.authors_dedupe),
+            max_contributors: self.max_contributors.or(default.max_contributors),
+            author_from_codeowners: self
+                .author_from_codeowners
+                .or(default.author_from_codeowners),
+            tokens: match (self.tokens, &default.tokens) {
+                (Some(mut tokens), Some(default)) => {
+                    for (key, value) in default {
+                        tokens.entry(key.clone()).or_insert(value.clone());
+                    }
+                    Some(tokens)
+                }
+                (tokens, default) => tokens.or(default.clone()),
+            },
+        }
+    }
+}
+
+/// Header template, global or language specific.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Template {
+    /// Languages for which the template applies ("*" for default): a single
+    /// one (`name = "c"`) or several (`name = ["c", "cpp", "objective-c"]`).
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub name: Vec<String>,
+    /// String put at the beginning of every line in the header.
+    pub prefix: Option<String>,
+    /// Strings added before the header (such as shebangs for example). A
+    /// `"..."` item stands for the default template’s ones.
+    pub before: Option<Vec<String>>,
+    /// Strings added after the header. A `"..."` item stands for the default
+    /// template’s ones.
+    pub after: Option<Vec<String>>,
+    /// Fill the tokens of the `after` lines, and refresh them whenever the
+    /// header is updated (a footer holding `#date_now` for example), instead
+    /// of writing them once as they are.
+    pub refresh_after: Option<bool>,
+    /// Value of the header template.
+    pub template: Option<String>,
+    /// Lines that should be updated when an existing header is updated. A
+    /// `"..."` item stands for the default template’s ones
+    /// (`["...", "Reviewed: "]` adds a tracked line to them).
+    pub track_changes: Option<Vec<String>>,
+    /// Text identifying the creation date line of existing headers, which is
+    /// never compared (by default, the text before `#file_creation` in the
+    /// template, such as `Creation date: `).
+    pub creation_line_marker: Option<String>,
+    /// Whether the lines holding the `#file_relative_path` token are updated
+    /// along with the tracked ones (defaults to `true`), so that the path stays
+    /// accurate after the file is moved.
+    pub track_path: Option<bool>,
+    /// Whether `#file_relative_path` is written with forward slashes whatever
+    /// the platform (defaults to `true`), so that headers generated on Windows
+    /// are identical to the others.
+    pub forward_slashes: Option<bool>,
+    /// Number of lines of the generated header (checked against it), so that
+    /// only that many lines are read at the top of files to check their header
+    /// (`--check`, `--dry-run`), the whole files being read when the header
+    /// isn’t up to date.
+    pub header_lines: Option<usize>,
+    /// Optional sentinel line emitted at the end of the header, used to
+    /// reliably locate the header region in existing files.
+    pub end_marker: Option<String>,
+    /// Line opening a block comment around the header (`/*` for example).
+    pub block_start: Option<String>,
+    /// String closing the block comment around the header (`*/` for example).
+    pub block_end: Option<String>,
+    /// Whether the closing string of the block comment is put on its own line
+    /// (the default), or after the last line of the header.
+    pub comment_close_on_own_line: Option<bool>,
+    /// Whether the dates use the full or abbreviated names of the days and
+    /// months (`long` by default).
+    pub date_style: Option<DateStyle>,
+    /// Capitalize the names of the days and months (`Mercredi` instead of
+    /// `mercredi`); by default, they are written as the locale does.
+    pub date_capitalize: Option<bool>,
+    /// Character encoding of the files (`latin1`, `shift_jis`, … UTF-8 by
+    /// default), used to read them and write them back.
+    pub encoding: Option<String>,
+    /// Shell command the generated header is piped through: it receives the
+    /// header lines on its standard input, and must print the transformed
+    /// header on its standard output.
+    pub post_process: Option<String>,
+    /// Regular expression matching the mandatory leading lines of the files
+    /// (pragmas, generated banners): the header is inserted right after the
+    /// last of the leading lines matching it (blank lines between them
+    /// included).
+    pub insert_after: Option<String>,
+    /// Number of lines at the top of the files that must stay there (a fixed
+    /// generated banner), the header being inserted after them (and after the
+    /// lines matching `insert_after` that follow).
+    pub protect_leading_lines: Option<usize>,
+    /// Keep the shebang of the files (`#!/usr/bin/env python3`) on their first
+    /// line, along with the encoding declaration following it, if any
+    /// (`# -*- coding: utf-8 -*-`), the header being inserted after them. On
+    /// by default. The shebang of the `before` lines, if any, is only written
+    /// in the files that have none.
+    pub keep_shebang: Option<bool>,
+    /// Number of blank lines put before the header (after the lines matching
+    /// `insert_after`, if any), the existing ones being replaced whenever the
+    /// header is written. By default, the existing blank lines are kept.
+    pub blank_lines_before: Option<usize>,
+    /// Names of templates whose text is added after this one’s (a license
+    /// block shared by several languages for example), their own includes
+    /// being added after them. A `"..."` item stands for the default
+    /// template’s ones.
+    pub include: Option<Vec<String>>,
+    /// Where the header is put in the files: at the top (the default), or at
+    /// the bottom as a footer.
+    pub position: Option<Position>,
+    /// Create headers in the files of the template’s languages, overriding
+    /// the project’s and global `create` (`create = false` for Markdown, so that
+    /// only the existing headers are updated for example).
+    pub create: Option<bool>,
+    /// Update the headers of the files of the template’s languages, overriding
+    /// the project’s and global `update`.
+    pub update: Option<bool>,
+}
+
+/// Handling of projects sharing the same root.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateProjects {
+    /// Warn about them, the first one being used.
+    #[default]
+    Warn,
+    /// Refuse the configuration.
+    Error,
+}
+
+/// Place of the header in the files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    /// At the beginning of the files (after the lines matching `insert_after`).
+    #[default]
+    Top,
+    /// At the end of the files, as a footer.
+    Bottom,
+}
+
+/// Names of the days and months used in the dates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateStyle {
+    /// Full names (`mercredi 16 août 2023`).
+    #[default]
+    Long,
+    /// Abbreviated names (`mer. 16 août 2023`).
+    Abbrev,
+}
+
+/// Item of a template’s list standing for the items of the default template.
+const INHERITED: &str = "...";
+
+/// Merges a list of a template with the default template’s one: the default
+/// list replaces the `"..."` items of the template’s, and is used as is if the
+/// template has none.
+///
+/// # Arguments
+/// * `list` - List of the template.
+/// * `default` - List of the default template.
+fn merge_list(list: Option<Vec<String>>, default: Option<&Vec<String>>) -> Vec<String> {
+    match list {
+        None => default.unwrap().clone(),
+        Some(list) => list
+            .into_iter()
+            .flat_map(|item| {
+                if item == INHERITED {
+                    default.cloned().unwrap_or_default()
+                } else {
+                    vec![item]
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Deserializes a string, or a list of strings.
+fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(name) => vec![name],
+        OneOrMany::Many(names) => names,
+    })
+}
+
+impl Template {
+    /// Checks if the template applies to a language (the case is ignored).
+    ///
+    /// # Arguments
+    /// * `language` - Language identifier.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::Template;
+    ///
+    /// let template = Template {
+    ///     name: vec![String::from("rust"), String::from("rs")],
+    ///     ..Default::default()
+    /// };
+    /// assert!(template.applies_to("Rust"));
+    /// assert!(!template.applies_to("python"));
+    /// ```
+    pub fn applies_to(&self, language: &str) -> bool {
+        self.name
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(language))
+    }
+
+    /// Checks that the template holds every field required to complete
+    /// another one in a merge, and gets the names of the missing ones if not.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::Template;
+    ///
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// assert!(config.default.is_complete().is_ok());
+    /// let template = Template { track_changes: None, ..config.default };
+    /// assert_eq!(template.is_complete(), Err(vec!["track_changes"]));
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn is_complete(&self) -> std::result::Result<(), Vec<&'static str>> {
+        let fields = [
+            ("prefix", self.prefix.is_some()),
+            ("before", self.before.is_some()),
+            ("after", self.after.is_some()),
+            ("template", self.template.is_some()),
+            ("track_changes", self.track_changes.is_some()),
+        ];
+        let missing: Vec<&'static str> = fields
+            .iter()
+            .filter(|(_, present)| !present)
+            .map(|(field, _)| *field)
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Merge the current template with the one by default.
+    ///
+    /// The default template must be complete (see `is_complete`).
+    ///
+    /// # Arguments
+    /// * `default` - `Template` by default, which will be used to fill any
+    ///   missing values in the language specific template.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::get_language_config;
+    ///
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// let template = get_language_config(&config, "rust").unwrap().merge(&config.default);
+    /// assert_eq!(template.prefix.as_deref(), Some("// "));
+    /// assert_eq!(template.template, config.default.template);
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn merge(self, default: &Template) -> Self {
+        Self {
+            name: self.name,
+            prefix: self.prefix.or(default.prefix.clone()),
+            before: Some(merge_list(self.before, default.before.as_ref())),
+            after: Some(merge_list(self.after, default.after.as_ref())),
+            template: self.template.or(default.template.clone()),
+            track_changes: Some(merge_list(
+                self.track_changes,
+                default.track_changes.as_ref(),
+            )),
+            creation_line_marker: self
+                .creation_line_marker
+                .or(default.creation_line_marker.clone()),
+            track_path: self.track_path.or(default.track_path),
+            forward_slashes: self.forward_slashes.or(default.forward_slashes),
+            header_lines: self.header_lines.or(default.header_lines),
+            end_marker: self.end_marker.or(default.end_marker.clone()),
+            block_start: self.block_start.or(default.block_start.clone()),
+            block_end: self.block_end.or(default.block_end.clone()),
+            comment_close_on_own_line: self
+                .comment_close_on_own_line
+                .or(default.comment_close_on_own_line),
+            date_style: self.date_style.or(default.date_style),
+            date_capitalize: self.date_capitalize.or(default.date_capitalize),
+            encoding: self.encoding.or(default.encoding.clone()),
+            post_process: self.post_process.or(default.post_process.clone()),
+            insert_after: self.insert_after.or(default.insert_after.clone()),
+            protect_leading_lines: self.protect_leading_lines.or(default.protect_leading_lines),
+            keep_shebang: self.keep_shebang.or(default.keep_shebang),
+            blank_lines_before: self.blank_lines_before.or(default.blank_lines_before),
+            position: self.position.or(default.position),
+            refresh_after: self.refresh_after.or(default.refresh_after),
+            create: self.create.or(default.create),
+            update: self.update.or(default.update),
+            include: match self.include {
+                Some(include) => Some(merge_list(Some(include), default.include.as_ref())),
+                None => default.include.clone(),
+            },
+        }
+    }
+
+    /// Adds the project specific `before` and `after` lines to a merged template.
+    ///
+    /// # Arguments
+    /// * `project` - Project the file belongs to.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::{Project, Template};
+    ///
+    /// let template = Template {
+    ///     before: Some(vec![String::from("/*")]),
+    ///     after: Some(vec![String::from("*/")]),
+    ///     ..Default::default()
+    /// };
+    /// let project = Project {
+    ///     before: Some(vec![String::from("Project: auto-header")]),
+    ///     after: Some(vec![String::new()]),
+    ///     ..Default::default()
+    /// };
+    /// let template = template.with_project(&project);
+    /// assert_eq!(template.before.unwrap(), ["/*", "Project: auto-header"]);
+    /// assert_eq!(template.after.unwrap(), ["", "*/"]);
+    /// ```
+    pub fn with_project(self, project: &Project) -> Self {
+        let mut before = self.before.unwrap_or_default();
+        before.extend(project.before.clone().unwrap_or_default());
+        let mut after = project.after.clone().unwrap_or_default();
+        after.extend(self.after.unwrap_or_default());
+        Self {
+            before: Some(before),
+            after: Some(after),
+            ..self
+        }
+    }
+
+    /// Applies the template overrides of the `.auto-header.toml` files to a
+    /// merged template.
+    ///
+    /// # Arguments
+    /// * `locals` - Directory configurations applying to the file, farthest first.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::{LocalConfig, Template};
+    ///
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// let local = LocalConfig {
+    ///     template: Some(Template {
+    ///         template: Some(String::from("File: #file_relative_path")),
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let template = config.default.clone().with_locals(&[local]);
+    /// assert_eq!(template.template.as_deref(), Some("File: #file_relative_path"));
+    /// assert_eq!(template.prefix, config.default.prefix);
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn with_locals(self, locals: &[LocalConfig]) -> Self {
+        locals
+            .iter()
+            .filter_map(|local| local.template.as_ref())
+            .fold(self, |template, local| Self {
+                name: template.name.clone(),
+                ..local.clone().merge(&template)
+            })
+    }
+
+    /// Gets the text identifying the creation date line of existing headers:
+    /// the configured marker, or the literal text before `#file_creation` in
+    /// the template.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::Template;
+    ///
+    /// let template = Template {
+    ///     template: Some(String::from("File: #file_relative_path\nCreated: #file_creation")),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(template.creation_line_marker().as_deref(), Some("Created:"));
+    /// let template = Template {
+    ///     creation_line_marker: Some(String::from("Since ")),
+    ///     ..template
+    /// };
+    /// assert_eq!(template.creation_line_marker().as_deref(), Some("Since"));
+    /// ```
+    pub fn creation_line_marker(&self) -> Option<String> {
+        if let Some(marker) = &self.creation_line_marker {
+            return Some(marker.trim_end().to_owned()).filter(|marker| !marker.is_empty());
+        }
+        self.template
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .find_map(|line| line.split_once("#file_creation"))
+            .map(|(literal, _)| literal.split('#').next().unwrap_or_default().trim())
+            .filter(|literal| !literal.is_empty())
+            .map(str::to_owned)
+    }
+
+    /// Gets the text identifying the signature line of existing headers: the
+    /// literal text before `#signature` in the template.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::Template;
+    ///
+    /// let template = Template {
+    ///     template: Some(String::from("File: #file_relative_path\nSignature: #signature")),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(template.signature_marker().as_deref(), Some("Signature:"));
+    /// assert_eq!(Template::default().signature_marker(), None);
+    /// ```
+    pub fn signature_marker(&self) -> Option<String> {
+        self.template
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .find_map(|line| line.split_once("#signature"))
+            .map(|(literal, _)| literal.split('#').next().unwrap_or_default().trim())
+            .filter(|literal| !literal.is_empty())
+            .map(str::to_owned)
+    }
+
+    /// Gets the beginnings of the lines that are updated in existing headers:
+    /// the `track_changes` ones, the signature line, and unless disabled, the
+    /// literal text before `#file_relative_path` on the lines holding it
+    /// (`File: ` for `File: #file_relative_path`), whatever the path currently
+    /// written.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::Template;
+    ///
+    /// let template = Template {
+    ///     template: Some(String::from("Path: #file_relative_path\nModified: #date_now")),
+    ///     track_changes: Some(vec![String::from("Modified: ")]),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(template.tracked_lines(), ["Modified: ", "Path: "]);
+    /// ```
+    pub fn tracked_lines(&self) -> Vec<String> {
+        let mut tracked = self.track_changes.clone().unwrap_or_default();
+        if let Some(marker) = self.signature_marker() {
+            if !tracked.iter().any(|t| marker.starts_with(t.as_str())) {
+                tracked.push(marker);
+            }
+        }
+        if !self.track_path.unwrap_or(true) {
+            return tracked;
+        }
+        let template = self.template.clone().unwrap_or_default();
+        for line in template.lines() {
+            let Some((literal, _)) = line.split_once("#file_relative_path") else {
+                continue;
+            };
+            // Only the text before any other token is known in advance.
+            let literal = literal.split('#').next().unwrap_or_default();
+            if !literal.trim().is_empty()
+                && !tracked.iter().any(|t| literal.starts_with(t.as_str()))
+            {
+                tracked.push(literal.to_owned());
+            }
+        }
+        tracked
+    }
+}
+
+/// Project configuration.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Project {
+    /// Root path of the project.
+    pub root: String,
+    /// Name of the project.
+    pub name: Option<String>,
+    /// Controls wether or not an existing header should be updated for this project.
+    pub create: Option<bool>,
+    /// Controls wether or not an existing header should be updated for this project.
+    pub update: Option<bool>,
+    /// Don’t create headers in empty files for this project (overrides the
+    /// global `skip_empty_files`).
+    pub skip_empty_files: Option<bool>,
+    /// Take the dates and author of the files from their git history for this
+    /// project (overrides the global `use_git`).
+    pub use_git: Option<bool>,
+    /// Locale to format the date with on this project.
+    pub locale: Option<String>,
+    /// Data specific to this project.
+    pub data: Option<ConfigData>,
+    /// Copyright notice (or license name) of this project, overriding the
+    /// one of its data and of the global data.
+    pub copyright_notice: Option<String>,
+    /// Strings added before the header for this project. They are appended
+    /// to the template’s `before` lines (which thus stay first, shebangs
+    /// for example), never replace them.
+    pub before: Option<Vec<String>>,
+    /// Strings added after the header for this project. They are inserted
+    /// before the template’s `after` lines, never replace them.
+    pub after: Option<Vec<String>>,
+}
+
+impl Project {
+    /// Creates a project discovered through its root marker.
+    ///
+    /// # Arguments
+    /// * `root` - Directory holding the root marker.
+    /// * `parent` - Project the settings are inherited from.
+    fn from_marker(root: &Path, parent: Project) -> Self {
+        Self {
+            root: root.to_string_lossy().into_owned(),
+            name: None,
+            ..parent
+        }
+    }
+
+    /// Resolves the project’s settings against the global configuration: the
+    /// data are merged with the global ones (the project’s copyright notice
+    /// taking precedence), named licenses are expanded, and
+    /// the locale falls back to English if it isn’t supported.
+    ///
+    /// # Arguments
+    /// * `config` - Global configuration.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::find_project;
+    ///
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// let project = find_project(&config, "/home/user/project/src/main.rs")?
+    ///     .unwrap()
+    ///     .resolve(&config);
+    /// assert_eq!(project.data.unwrap().author.as_deref(), Some("Jane Doe"));
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn resolve(mut self, config: &Config) -> Self {
+        let locale = self.locale.unwrap_or(config.locale.clone());
+        self.locale = Some(if is_supported_locale(&locale) {
+            locale
+        } else {
+            default_locale()
+        });
+
+        let mut data = if let Some(data) = self.data {
+            data.merge(&config.data)
+        } else {
+            config.data.clone()
+        };
+        if let Some(notice) = self.copyright_notice.clone() {
+            data.copyright_notice = Some(notice);
+        }
+        if let Some(notice) = data.copyright_notice {
+            data.copyright_notice = Some(get_license(&notice).to_owned());
+        }
+        self.data = Some(data);
+        self
+    }
+
+    /// Applies the overrides of the `.auto-header.toml` files to a resolved
+    /// project.
+    ///
+    /// # Arguments
+    /// * `locals` - Directory configurations applying to the file, farthest first.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::config::{find_project, ConfigData, LocalConfig};
+    ///
+    /// # let config = auto_header::config::Config::parse(r##"
+    /// # [data]
+    /// # author = "Jane Doe"
+    /// # [default]
+    /// # name = "*"
+    /// # prefix = "# "
+    /// # before = []
+    /// # after = [""]
+    /// # template = "File: #file_relative_path\nAuthor: #author"
+    /// # track_changes = ["File: "]
+    /// # [[language]]
+    /// # name = "rust"
+    /// # prefix = "// "
+    /// # [[project]]
+    /// # root = "/home/user/project"
+    /// # "##)?;
+    /// let project = find_project(&config, "/home/user/project/src/main.rs")?
+    ///     .unwrap()
+    ///     .resolve(&config);
+    /// let local = LocalConfig {
+    ///     update: Some(false),
+    ///     data: Some(ConfigData {
+    ///         author: Some(String::from("John Smith")),
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let project = project.with_locals(&[local]);
+    /// assert_eq!(project.update, Some(false));
+    /// assert_eq!(project.data.unwrap().author.as_deref(), Some("John Smith"));
+    /// # Ok::<(), auto_header::error::AutoHeaderError>(())
+    /// ```
+    pub fn with_locals(mut self, locals: &[LocalConfig]) -> Self {
+        for local in locals {
+            self.create = local.create.or(self.create);
+            self.update = local.update.or(self.update);
+            if let (Some(data), Some(project_data)) = (&local.data, &self.data) {
+                let mut data = data.clone().merge(project_data);
+                if let Some(notice) = data.copyright_notice {
+                    data.copyright_notice = Some(get_license(&notice).to_owned());
+                }
+                self.data = Some(data);
+            }
+        }
+        self
+    }
+}
+
+/// Name of the files holding directory specific settings.
+pub const LOCAL_CONFIG: &str = ".auto-header.toml";
+
+/// Settings of a `.auto-header.toml` file, overriding those of the project for
+/// the files of its directory subtree.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct LocalConfig {
+    /// Controls wether or not a header should be created if absent.
+    pub create: Option<bool>,
+    /// Controls wether or not an existing header should be updated.
+    pub update: Option<bool>,
+    /// Data overriding those of the project.
+    pub data: Option<ConfigData>,
+    /// Template overriding the language’s one.
+    pub template: Option<Template>,
+}
+
+impl LocalConfig {
+    /// Gets the directory configurations applying to a file: the
+    /// `.auto-header.toml` files found in its directory and its parents, up to
+    /// the project’s root. They are sorted from the farthest to the closest,
+    /// closer ones taking precedence.
+    ///
+    /// # Arguments
+    /// * `project` - Project the file belongs to.
+    /// * `path` - Path to the file.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let locals = LocalConfig::find(&project, "./src/main.rs")?;
+    /// ```
+    pub fn find(project: &Project, path: &str) -> Result<Vec<Self>> {
+        let path = absolute_path(Path::new(path))?;
+        let mut locals = Vec::new();
+        for dir in path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&project.root))
+        {
+            let file = dir.join(LOCAL_CONFIG);
+            if !file.is_file() {
+                continue;
+            }
+            let file = file.to_string_lossy();
+            let content =
+                fs::read_to_string(file.as_ref()).map_err(AutoHeaderError::read(&file))?;
+            locals.push(toml::from_str(&content)?);
+        }
+        locals.reverse();
+        Ok(locals)
+    }
+}
+
+/// Get the file’s language from the configuration’s `extensions` (by file
+/// name, then by extension), or else from its extension (whatever its case:
+/// `Main.RS` is a rust file).
+///
+/// # Arguments
+/// * `config` - Global configuration.
+/// * `path` - path to the file to format.
+///
+/// # Example
+/// ```
+/// use auto_header::config::get_language;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// assert_eq!(get_language(&config, "./src/Main.RS"), "rust");
+/// assert_eq!(get_language(&config, "./docs/index.rst"), "restructuredtext");
+/// assert_eq!(get_language(&config, "./LICENSE"), "*");
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn get_language(config: &Config, path: &str) -> String {
+    let file = Path::new(path);
+    let mapped = config.extensions.as_ref().and_then(|extensions| {
+        let name = file.file_name()?.to_str()?;
+        extensions.get(name).or_else(|| {
+            let extension = file.extension()?.to_str()?;
+            extensions
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(extension))
+                .map(|(_, language)| language)
+        })
+    });
+    if let Some(language) = mapped {
+        return language.clone();
+    }
+    // reStructuredText isn’t detected, while it has a built-in template.
+    let extension = file.extension().and_then(|extension| extension.to_str());
+    if extension.is_some_and(|extension| extension.eq_ignore_ascii_case("rst")) {
+        return String::from("restructuredtext");
+    }
+    String::from(match from_path(path) {
+        Some(lang) => lang.id(),
+        None => "*",
+    })
+}
+
+/// Get the language specific configuration.
+///
+/// # Arguments
+/// * `config` - Configuration.
+/// * `language` - Language for which we want the configuration (the case is ignored).
+///
+/// # Example
+/// ```
+/// use auto_header::config::get_language_config;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// assert_eq!(get_language_config(&config, "Rust").unwrap().prefix.as_deref(), Some("// "));
+/// assert_eq!(get_language_config(&config, "python").unwrap().prefix.as_deref(), Some("# "));
+/// assert_eq!(get_language_config(&config, "markdown").unwrap().block_start.as_deref(), Some("<!--"));
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn get_language_config(config: &Config, language: &str) -> Option<Template> {
+    let res = config
+        .language
+        .iter()
+        .flatten()
+        .find(|t| t.applies_to(language));
+    match res {
+        Some(res) => Some(res.clone()),
+        None => prose_template(language).or_else(|| {
+            (config.language.is_none() || !config.language_strict).then(|| config.default.clone())
+        }),
+    }
+}
+
+/// Gets the built-in template of a prose language (Markdown, reStructuredText),
+/// in which the header is a comment block rather than prefixed lines. It’s only
+/// used when no template of the configuration applies to the language (even
+/// with `language_strict`), and is completed by the default template.
+///
+/// # Arguments
+/// * `language` - Language of the file (the case is ignored).
+///
+/// # Example
+/// ```
+/// use auto_header::config::prose_template;
+///
+/// let template = prose_template("Markdown").unwrap();
+/// assert_eq!(template.block_start.as_deref(), Some("<!--"));
+/// assert_eq!(template.block_end.as_deref(), Some("-->"));
+/// assert!(prose_template("rust").is_none());
+/// ```
+pub fn prose_template(language: &str) -> Option<Template> {
+    let (block_start, prefix, block_end) = match language.to_lowercase().as_str() {
+        "markdown" => ("<!--", "", Some("-->")),
+        "restructuredtext" | "rst" => ("..", "   ", None),
+        _ => return None,
+    };
+    Some(Template {
+        name: vec![language.to_owned()],
+        prefix: Some(prefix.to_owned()),
+        block_start: Some(block_start.to_owned()),
+        block_end: block_end.map(str::to_owned),
+        ..Default::default()
+    })
+}
+
+/// Get a template by its name, regardless of the file’s language.
+///
+/// # Arguments
+/// * `config` - Global configuration.
+/// * `name` - Name of the template.
+///
+/// # Example
+/// ```
+/// use auto_header::config::get_named_template;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// assert_eq!(get_named_template(&config, "rust").unwrap().prefix.as_deref(), Some("// "));
+/// assert_eq!(get_named_template(&config, "*").unwrap().prefix.as_deref(), Some("# "));
+/// assert!(get_named_template(&config, "python").is_none());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn get_named_template(config: &Config, name: &str) -> Option<Template> {
+    if config.default.name.iter().any(|n| n == name) {
+        return Some(config.default.clone());
+    }
+    config
+        .language
+        .as_ref()?
+        .iter()
+        .find(|t| t.name.iter().any(|n| n == name))
+        .cloned()
+}
+
+/// Given the path of the considered file, gets the project’s configuration
+/// if it exists. If several projects have the same root, the first one wins.
+///
+/// # Arguments
+/// * `config` - Global configuration.
+/// * `path` - Path to the file for which to create or update the header.
+///
+/// # Example
+/// ```
+/// use auto_header::config::find_project;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// let project = find_project(&config, "/home/user/project/src/main.rs")?;
+/// assert_eq!(project.unwrap().root, "/home/user/project");
+/// assert!(find_project(&config, "/home/user/elsewhere/main.rs")?.is_none());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn find_project(config: &Config, path: &str) -> Result<Option<Project>> {
+    let projects = config.project.as_deref().unwrap_or_default();
+    if projects.is_empty() && config.root_marker.is_none() {
+        return Ok(None);
+    }
+    let path = absolute_path(Path::new(path))?;
+    let mut marker_root = None;
+    for dir in path.ancestors() {
+        if let Some(project) = projects.iter().find(|p| Path::new(&p.root) == dir) {
+            return Ok(Some(match marker_root {
+                Some(root) => Project::from_marker(root, project.clone()),
+                None => project.clone(),
+            }));
+        }
+        if marker_root.is_none()
+            && config
+                .root_marker
+                .as_ref()
+                .is_some_and(|marker| dir.join(marker).exists())
+        {
+            marker_root = Some(dir);
+        }
+    }
+    Ok(marker_root.map(|root| Project::from_marker(root, Project::default())))
+}
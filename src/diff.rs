@@ -0,0 +1,226 @@
+/// Number of unchanged lines shown around the changes.
+const CONTEXT: usize = 3;
+
+/// Operation turning a line of the old content into the new one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    /// Line present in both contents.
+    Equal(usize, usize),
+    /// Line of the old content removed.
+    Delete(usize),
+    /// Line of the new content inserted.
+    Insert(usize),
+}
+
+/// Lines of a content, as written in a diff.
+struct DiffLines<'a> {
+    /// Lines, without their line feed.
+    lines: &'a [String],
+    /// Whether the last line ends with a line feed.
+    final_newline: bool,
+}
+
+impl<'a> DiffLines<'a> {
+    /// Splits the lines of a file (as read by `read_lines`) between the lines
+    /// themselves and the empty string following the last line feed.
+    fn new(content: &'a [String]) -> Self {
+        match content.split_last() {
+            Some((last, lines)) if last.is_empty() => Self {
+                lines,
+                final_newline: true,
+            },
+            _ => Self {
+                lines: content,
+                final_newline: false,
+            },
+        }
+    }
+
+    /// Writes a line of the diff, flagging the last line if it has no line feed.
+    fn push(&self, diff: &mut String, marker: char, index: usize) {
+        diff.push(marker);
+        diff.push_str(&self.lines[index]);
+        diff.push('\n');
+        if index + 1 == self.lines.len() && !self.final_newline {
+            diff.push_str("\\ No newline at end of file\n");
+        }
+    }
+}
+
+/// Computes the unified diff between two versions of a file, in the format
+/// expected by `git apply`. Nothing is returned if they’re identical.
+///
+/// # Arguments
+/// * `path` - Path of the file, relative to the root of the repository.
+/// * `old` - Lines of the file (as read by `read_lines`).
+/// * `new` - Lines of the modified file.
+///
+/// # Example
+/// ```
+/// use auto_header::diff::unified_diff;
+///
+/// let content = vec![String::from("fn main() {}"), String::new()];
+/// let new_content = vec![
+///     String::from("// File: src/main.rs"),
+///     String::from("fn main() {}"),
+///     String::new(),
+/// ];
+/// let diff = unified_diff("src/main.rs", &content, &new_content);
+/// assert!(diff.starts_with("diff --git a/src/main.rs b/src/main.rs\n"));
+/// assert!(diff.contains("\n+// File: src/main.rs\n fn main() {}\n"));
+/// assert!(unified_diff("src/main.rs", &content, &content).is_empty());
+/// ```
+pub fn unified_diff(path: &str, old: &[String], new: &[String]) -> String {
+    if old == new {
+        return String::new();
+    }
+    let path = path.trim_start_matches("./");
+    let old = DiffLines::new(old);
+    let new = DiffLines::new(new);
+    let ops = diff_ops(old.lines, new.lines);
+
+    let mut diff = format!(
+        "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n",
+        path = path
+    );
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut hunk_start = 0;
+    while hunk_start < changes.len() {
+        // Changes closer than twice the context belong to the same hunk.
+        let mut hunk_end = hunk_start;
+        while hunk_end + 1 < changes.len()
+            && changes[hunk_end + 1] - changes[hunk_end] <= 2 * CONTEXT + 1
+        {
+            hunk_end += 1;
+        }
+        let first = changes[hunk_start].saturating_sub(CONTEXT);
+        let last = (changes[hunk_end] + CONTEXT).min(ops.len() - 1);
+        write_hunk(&mut diff, &ops[first..=last], &old, &new);
+        hunk_start = hunk_end + 1;
+    }
+    diff
+}
+
+/// Writes a hunk of the diff.
+fn write_hunk(diff: &mut String, ops: &[Op], old: &DiffLines, new: &DiffLines) {
+    let old_start = ops.iter().find_map(|op| match op {
+        Op::Equal(i, _) | Op::Delete(i) => Some(*i),
+        Op::Insert(_) => None,
+    });
+    let new_start = ops.iter().find_map(|op| match op {
+        Op::Equal(_, j) | Op::Insert(j) => Some(*j),
+        Op::Delete(_) => None,
+    });
+    let old_count = ops.iter().filter(|op| !matches!(op, Op::Insert(_))).count();
+    let new_count = ops.iter().filter(|op| !matches!(op, Op::Delete(_))).count();
+    // A side without any line in the hunk is empty (the context would be on it otherwise).
+    let range = |start: Option<usize>, count: usize| match start {
+        Some(start) => format!("{},{}", start + 1, count),
+        None => String::from("0,0"),
+    };
+    diff.push_str(&format!(
+        "@@ -{} +{} @@\n",
+        range(old_start, old_count),
+        range(new_start, new_count)
+    ));
+    for op in ops {
+        match *op {
+            Op::Equal(i, _) => old.push(diff, ' ', i),
+            Op::Delete(i) => old.push(diff, '-', i),
+            Op::Insert(j) => new.push(diff, '+', j),
+        }
+    }
+}
+
+/// Computes the operations turning the old lines into the new ones (longest
+/// common subsequence of the lines, after removal of the common beginning and
+/// end, which are most of the file when a header is added or updated).
+fn diff_ops(old: &[String], new: &[String]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // lengths[i][j]: length of the longest common subsequence of old_middle[i..] and new_middle[j..].
+    let mut lengths = vec![vec![0usize; new_middle.len() + 1]; old_middle.len() + 1];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i][j] = if old_middle[i] == new_middle[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            ops.push(Op::Equal(prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if j < new_middle.len()
+            && (i == old_middle.len() || lengths[i][j + 1] > lengths[i + 1][j])
+        {
+            ops.push(Op::Insert(prefix + j));
+            j += 1;
+        } else {
+            ops.push(Op::Delete(prefix + i));
+            i += 1;
+        }
+    }
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    ops.extend((0..suffix).map(|k| Op::Equal(old_end + k, new_end + k)));
+    ops
+}
+
+/// Colors a unified diff with ANSI codes, the way `git diff` does: the file
+/// headers in bold, the hunk headers in cyan, the removed lines in red and the
+/// added ones in green.
+///
+/// # Arguments
+/// * `diff` - Unified diff.
+///
+/// # Example
+/// ```
+/// use auto_header::diff::colorize;
+///
+/// let diff = "@@ -1 +1,2 @@\n+// File: src/main.rs\n fn main() {}\n";
+/// assert_eq!(
+///     colorize(diff),
+///     "\x1b[36m@@ -1 +1,2 @@\x1b[0m\n\x1b[32m+// File: src/main.rs\x1b[0m\n fn main() {}\n"
+/// );
+/// ```
+pub fn colorize(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let code = if line.starts_with("diff --git")
+                || line.starts_with("--- ")
+                || line.starts_with("+++ ")
+            {
+                "1"
+            } else if line.starts_with("@@") {
+                "36"
+            } else if line.starts_with('-') {
+                "31"
+            } else if line.starts_with('+') {
+                "32"
+            } else {
+                return format!("{}\n", line);
+            };
+            format!("\x1b[{}m{}\x1b[0m\n", code, line)
+        })
+        .collect()
+}
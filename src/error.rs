@@ -0,0 +1,227 @@
+use std::io;
+use thiserror::Error;
+
+/// Errors that can occur while creating or updating a header.
+#[derive(Debug, Error)]
+pub enum AutoHeaderError {
+    /// The configuration file doesn’t exist.
+    #[error("Configuration file {0} does not exist.")]
+    ConfigNotFound(String),
+    /// The configuration file couldn’t be parsed.
+    #[error("Error reading configuration file: {0}")]
+    ConfigParse(#[from] toml::de::Error),
+    /// The configuration file can’t be parsed.
+    #[error("Invalid configuration file {path}: {reason}")]
+    InvalidConfig {
+        /// Path of the configuration file.
+        path: String,
+        /// Why it can’t be parsed.
+        reason: String,
+    },
+    /// The format of the configuration file can’t be handled.
+    #[error("Unknown format for configuration file {0}: expected a .toml extension.")]
+    ConfigFormat(String),
+    /// A field required to build the header is missing from the configuration.
+    #[error("Missing field {0} in the configuration.")]
+    MissingField(String),
+    /// Some tokens of the templates can’t be filled with the configuration’s data.
+    #[error("{0} unresolved token(s) in the configuration.")]
+    UnresolvedTokens(usize),
+    /// Several projects of the configuration have the same root.
+    #[error("Several projects have the root {0} in the configuration.")]
+    DuplicateProject(String),
+    /// The target file doesn’t exist.
+    #[error("File {0} does not exist.")]
+    FileNotFound(String),
+    /// No project is configured for the file.
+    #[error("No configuration found for file {0}.")]
+    NoProject(String),
+    /// No project is configured for the content read from the standard input.
+    #[error("No configuration found for the standard input: use --stdin-filename to give its path in a project.")]
+    NoStdinProject,
+    /// No template applies to the file’s language.
+    #[error("No configuration found for file {path} (language {language}).")]
+    NoLanguage {
+        /// Path of the file.
+        path: String,
+        /// Language detected for the file.
+        language: String,
+    },
+    /// No template has the requested name.
+    #[error("No template named {0} found in the configuration.")]
+    NoTemplate(String),
+    /// Templates include each other.
+    #[error("Templates include each other: {0}.")]
+    IncludeCycle(String),
+    /// The file couldn’t be read.
+    #[error("Failed to read {path}: {source}")]
+    Read {
+        /// Path of the file.
+        path: String,
+        /// Underlying error.
+        source: io::Error,
+    },
+    /// The file couldn’t be written.
+    #[error("Failed to write {path}: {source}")]
+    Write {
+        /// Path of the file.
+        path: String,
+        /// Underlying error.
+        source: io::Error,
+    },
+    /// The file isn’t valid UTF-8.
+    #[error("File {0} is not valid UTF-8.")]
+    InvalidUtf8(String),
+    /// The file can’t be represented in the configured encoding.
+    #[error("File {path} is not valid {encoding}.")]
+    InvalidEncoding {
+        /// Path of the file.
+        path: String,
+        /// Name of the encoding.
+        encoding: String,
+    },
+    /// The configured encoding doesn’t exist.
+    #[error("Unknown encoding {0} in the configuration.")]
+    UnknownEncoding(String),
+    /// The file system doesn’t record the creation time of the file.
+    #[error("Creation date of {0} is unavailable on this file system.")]
+    MissingBirthtime(String),
+    /// A regular expression of the configuration is invalid.
+    #[error("Invalid regular expression {pattern} in the configuration: {reason}")]
+    InvalidRegex {
+        /// Regular expression.
+        pattern: String,
+        /// Why it’s invalid.
+        reason: String,
+    },
+    /// The file is read-only.
+    #[error("{0} is read-only, skipping (use --chmod to update it anyway).")]
+    ReadOnly(String),
+    /// The file is shorter than its header.
+    #[error("Header of {0} is damaged (file shorter than the header): skipping, use --force to recreate it.")]
+    DamagedHeader(String),
+    /// The header was edited by hand since it was written.
+    #[error("Header of {0} was edited by hand: skipping, use --force to overwrite it.")]
+    EditedHeader(String),
+    /// The file starts with a header written with another comment syntax.
+    #[error("Header of {0} uses another comment syntax: skipping, use --force to replace it.")]
+    ForeignHeader(String),
+    /// The header isn’t up to date (checked without modifying the file).
+    #[error("Header of {path} is not up to date (it would be {action}).")]
+    OutdatedHeader {
+        /// Path of the file.
+        path: String,
+        /// What would be done to the file.
+        action: crate::Action,
+    },
+    /// The header’s modification date is older than the file’s by more than
+    /// the configured maximum age.
+    #[error("Header of {path} is stale: the file was modified {days} days after the date written in it.")]
+    StaleHeader {
+        /// Path of the file.
+        path: String,
+        /// Number of days between the header’s date and the file’s.
+        days: i64,
+    },
+    /// The header generated for a sample file isn’t the expected one
+    /// (`--config-test`).
+    #[error("Header generated for {path} doesn’t match {expected}.")]
+    UnexpectedHeader {
+        /// Path of the sample file.
+        path: String,
+        /// Path of the file holding the expected header.
+        expected: String,
+    },
+    /// The format of the report file can’t be inferred from its extension.
+    #[error("Unknown format for report file {0}: expected a .json or .csv extension.")]
+    ReportFormat(String),
+    /// The `header_lines` hint of the template doesn’t match the generated header.
+    #[error("Template declares {hint} header lines, but the generated header has {actual}.")]
+    HeaderLines {
+        /// Number of lines declared in the template.
+        hint: usize,
+        /// Number of lines of the generated header.
+        actual: usize,
+    },
+    /// The command run at the end of a run (`on_complete`) failed.
+    #[error("Completion command `{command}` failed: {reason}")]
+    OnComplete {
+        /// Command that was run.
+        command: String,
+        /// Why it failed.
+        reason: String,
+    },
+    /// A directory can’t be watched.
+    #[error("Failed to watch {path}: {reason}")]
+    Watch {
+        /// Path of the directory.
+        path: String,
+        /// Why it can’t be watched.
+        reason: String,
+    },
+    /// A git command failed.
+    #[error("Git command `{command}` failed: {reason}")]
+    Git {
+        /// Command that was run.
+        command: String,
+        /// Why it failed.
+        reason: String,
+    },
+    /// The post-processing command of the header failed.
+    #[error("Post-processing command `{command}` failed: {reason}")]
+    PostProcess {
+        /// Command that was run.
+        command: String,
+        /// Why it failed.
+        reason: String,
+    },
+}
+
+impl AutoHeaderError {
+    /// Builds a closure wrapping an I/O error that occurred while reading a file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::error::AutoHeaderError;
+    /// use std::{fs, io};
+    ///
+    /// let path = "missing/main.rs";
+    /// let err = fs::read_to_string(path).map_err(AutoHeaderError::read(path)).unwrap_err();
+    /// assert!(matches!(&err, AutoHeaderError::Read { source, .. } if source.kind() == io::ErrorKind::NotFound));
+    /// assert!(err.to_string().starts_with("Failed to read missing/main.rs: "));
+    /// ```
+    pub fn read(path: &str) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::Read {
+            path: path.to_owned(),
+            source,
+        }
+    }
+
+    /// Builds a closure wrapping an I/O error that occurred while writing a file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::error::AutoHeaderError;
+    /// use std::{fs, io};
+    ///
+    /// let path = "missing/main.rs";
+    /// let err = fs::write(path, "fn main() {}\n").map_err(AutoHeaderError::write(path)).unwrap_err();
+    /// assert!(matches!(&err, AutoHeaderError::Write { source, .. } if source.kind() == io::ErrorKind::NotFound));
+    /// assert!(err.to_string().starts_with("Failed to write missing/main.rs: "));
+    /// ```
+    pub fn write(path: &str) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::Write {
+            path: path.to_owned(),
+            source,
+        }
+    }
+}
+
+/// Result type of the library.
+pub type Result<T> = std::result::Result<T, AutoHeaderError>;
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Local};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::error::{AutoHeaderError, Result};
+
+/// Gets the files of the current git repository that changed, relative to the
+/// current directory: those modified in the working tree (untracked files
+/// included) and / or those staged in the index. Deleted files are ignored.
+///
+/// # Arguments
+/// * `modified` - Include the files modified in the working tree, and the untracked ones.
+/// * `staged` - Include the files staged in the index.
+///
+/// # Example
+/// ```ignore
+/// for path in changed_files(true, true)? {
+///     process_file(&path, &project, &template, &options)?;
+/// }
+/// ```
+pub fn changed_files(modified: bool, staged: bool) -> Result<Vec<String>> {
+    let mut commands: Vec<&[&str]> = Vec::new();
+    if modified {
+        commands.push(&["diff", "-z", "--name-only", "--relative", "--diff-filter=d"]);
+        commands.push(&["ls-files", "-z", "--others", "--exclude-standard"]);
+    }
+    if staged {
+        commands.push(&[
+            "diff",
+            "-z",
+            "--cached",
+            "--name-only",
+            "--relative",
+            "--diff-filter=d",
+        ]);
+    }
+
+    let mut files: Vec<String> = Vec::new();
+    for args in commands {
+        // Paths are NUL separated, so that git doesn’t quote unusual names.
+        for file in git(args)?.split('\0').filter(|file| !file.is_empty()) {
+            if !files.iter().any(|f| f == file) {
+                files.push(file.to_owned());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Runs a git command in the current directory, and gets its output.
+///
+/// # Arguments
+/// * `args` - Arguments of the command.
+fn git(args: &[&str]) -> Result<String> {
+    let command = format!("git {}", args.join(" "));
+    let output = Command::new("git")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| AutoHeaderError::Git {
+            command: command.clone(),
+            reason: err.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(AutoHeaderError::Git {
+            command,
+            reason: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Commit changing a file.
+#[derive(Clone, Debug)]
+pub struct Commit {
+    /// Date the commit was authored.
+    pub date: DateTime<Local>,
+    /// Name of its author.
+    pub author: String,
+    /// Mail address of its author.
+    pub mail: String,
+}
+
+/// Gets the commits changing a file (its renames followed), the most recent
+/// first. Nothing is returned if git isn’t available, or if the file isn’t
+/// tracked.
+///
+/// # Arguments
+/// * `path` - Path of the file.
+///
+/// # Example
+/// ```ignore
+/// if let Some(first) = file_commits(Path::new("./src/main.rs")).last() {
+///     println!("created on {} by {}", first.date, first.author);
+/// }
+/// ```
+pub fn file_commits(path: &Path) -> Vec<Commit> {
+    let Some(output) = git_at(path, &["log", "--follow", "--format=%aI%x00%an%x00%ae"]) else {
+        return Vec::new();
+    };
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let date = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+            Some(Commit {
+                date: date.with_timezone(&Local),
+                author: fields.next()?.to_owned(),
+                mail: fields.next()?.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Checks whether a tracked file changed since its last commit (in the working
+/// tree or in the index).
+///
+/// # Arguments
+/// * `path` - Path of the file.
+pub fn changed_since_commit(path: &Path) -> bool {
+    git_at(path, &["diff", "HEAD", "--name-only"]).is_none_or(|output| !output.is_empty())
+}
+
+/// Runs a git command about a file, in its directory, and gets its output if
+/// it succeeded.
+///
+/// # Arguments
+/// * `path` - Path of the file, given last to the command.
+/// * `args` - Arguments of the command.
+fn git_at(path: &Path, args: &[&str]) -> Option<String> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let output = Command::new("git")
+        .current_dir(dir.unwrap_or(Path::new(".")))
+        .args(args)
+        .arg("--")
+        .arg(path.file_name()?)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    String::from_utf8(output.stdout).ok()
+}
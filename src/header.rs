@@ -0,0 +1,2189 @@
+use chrono::{DateTime, Local};
+use encoding_rs::{Encoding, UTF_8};
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, MAIN_SEPARATOR},
+    process::{Command, Stdio},
+    str,
+};
+
+use crate::{
+    codeowners::file_owners,
+    config::{default_locale, ConfigData, DateStyle, Project, Template},
+    error::{AutoHeaderError, Result},
+    git::{changed_since_commit, file_commits, Commit},
+    locales::{format_date, parse_date},
+    paths::absolute_path,
+};
+
+/// Format of the `#file_modified_iso` dates.
+const ISO_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// Dates used to fill a template.
+#[derive(Clone, Debug)]
+pub struct FileDates {
+    /// Creation date of the file.
+    pub created: DateTime<Local>,
+    /// Last modification date of the file.
+    pub modified: DateTime<Local>,
+    /// Current date.
+    pub now: DateTime<Local>,
+}
+
+impl FileDates {
+    /// Gets the dates of a file from its metadata. If the file system doesn’t
+    /// record creation times, the modification date is used instead, and the
+    /// current date if it doesn’t record modification times either. Only a
+    /// file whose metadata can’t be read is an error.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let dates = FileDates::from_file("./src/main.rs")?;
+    /// ```
+    pub fn from_file(path: &str) -> Result<Self> {
+        let metadata = fs::metadata(path).map_err(AutoHeaderError::read(path))?;
+        let now = Local::now();
+        let modified = metadata.modified().map_or(now, Into::into);
+        Ok(Self {
+            created: metadata.created().map_or(modified, Into::into),
+            modified,
+            now,
+        })
+    }
+
+    /// Gets the dates of a file from its git history: its creation date is the
+    /// one of the first commit changing it, and its modification date the one
+    /// of the last commit, unless it changed since. The dates of the file
+    /// system are used for what the history doesn’t give (untracked files, or
+    /// git being unavailable).
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let dates = FileDates::from_history("./src/main.rs")?;
+    /// ```
+    pub fn from_history(path: &str) -> Result<Self> {
+        Self::from_commits(path, &file_commits(Path::new(path)))
+    }
+
+    /// Gets the dates of a file from commits changing it (the most recent
+    /// first), already fetched from its history. See `from_history`.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    /// * `commits` - Commits changing the file, the most recent first.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let commits = file_commits(Path::new("./src/main.rs"));
+    /// let dates = FileDates::from_commits("./src/main.rs", &commits)?;
+    /// ```
+    pub fn from_commits(path: &str, commits: &[Commit]) -> Result<Self> {
+        let mut dates = Self::from_file(path)?;
+        if let (Some(last), Some(first)) = (commits.first(), commits.last()) {
+            dates.created = first.date;
+            if !changed_since_commit(Path::new(path)) {
+                dates.modified = last.date;
+            }
+        }
+        Ok(dates)
+    }
+
+    /// Dates of content that doesn’t come from a file (read from the standard
+    /// input for example): everything happens now.
+    pub fn now() -> Self {
+        Self::at(Local::now())
+    }
+
+    /// Dates of content that doesn’t come from a file, everything happening
+    /// at a given date.
+    ///
+    /// # Arguments
+    /// * `date` - Date of the content.
+    pub fn at(date: DateTime<Local>) -> Self {
+        Self {
+            created: date,
+            modified: date,
+            now: date,
+        }
+    }
+
+    /// Dates to use when the file is about to be modified: its modification date
+    /// becomes the current date.
+    pub fn touched(&self) -> Self {
+        Self {
+            modified: self.now,
+            ..self.clone()
+        }
+    }
+}
+
+/// Checks whether the file system records the creation time of a file.
+///
+/// # Arguments
+/// * `path` - Path of the file.
+///
+/// # Example
+/// ```ignore
+/// if !has_birthtime("./src/main.rs") {
+///     log.info("Creation date unavailable, using the modification date.");
+/// }
+/// ```
+pub fn has_birthtime(path: &str) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.created())
+        .is_ok()
+}
+
+/// Maximum number of substitution passes filling a template, bounding the
+/// expansion of tokens referencing each other.
+const SUBSTITUTION_PASSES: usize = 8;
+
+/// Stands for the `#` of the values written in a header while its template is
+/// filled, so that the following passes don’t take them for tokens.
+const ESCAPED_HASH: &str = "\u{e000}";
+
+/// Fills a template with generated or configured data.
+///
+/// Every pass of substitution replaces `#copyright_notice`, then `#author`,
+/// the other built-in tokens and finally the custom ones. Tokens found in the
+/// configured texts (copyright notice, author format, custom tokens) are
+/// filled by the following passes, until nothing changes or after
+/// `SUBSTITUTION_PASSES` passes (tokens referencing each other are then left).
+/// The values of the file (its path, its authors, its project’s name…) are
+/// written as they are, whatever `#` they hold.
+///
+/// # Arguments
+/// * `template` - Template to fill, resulting from the merge of global and language templates.
+/// * `project` - Information on the project the file belongs to.
+/// * `path` - Path of the file.
+/// * `root` - Path to the root of the project the file belongs to.
+/// * `dates` - Dates of the file.
+///
+/// # Example
+/// ```
+/// use auto_header::{header::{fill_template, FileDates}, resolve};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// let path = "/home/user/project/src/main.rs";
+/// let (project, template) = resolve(&config, path)?;
+/// let dates = FileDates::now();
+/// let header = fill_template(&template, &project, path, &project.root, &dates)?;
+/// assert_eq!(header, ["// File: src/main.rs", "// Author: Jane Doe", ""]);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn fill_template(
+    template: &Template,
+    project: &Project,
+    path: &str,
+    root: &str,
+    dates: &FileDates,
+) -> Result<Vec<String>> {
+    let path = absolute_path(Path::new(path))?;
+    let locale = project.locale.clone().unwrap_or(default_locale());
+    let capitalize = template.date_capitalize.unwrap_or(false);
+    let date_format = date_format(template);
+    let creation_date = format_date(&dates.created, date_format, &locale, capitalize);
+    let modification_date = format_date(
+        &dates.modified,
+        &format!("{} @ %H:%M:%S", date_format),
+        &locale,
+        capitalize,
+    );
+    let modification_iso = dates.modified.format(ISO_FORMAT).to_string();
+    // Offset of the time zone the modification date is written in (`Z` for UTC).
+    let tz_offset = match dates.modified.offset().local_minus_utc() {
+        0 => String::from("Z"),
+        _ => dates.modified.format("%:z").to_string(),
+    };
+    let year = dates.now.format("%Y").to_string();
+    let path = path.strip_prefix(root).unwrap_or(&path);
+    let data = project.data.as_ref().unwrap();
+    let owned = data
+        .author_from_codeowners
+        .unwrap_or(false)
+        .then(|| file_owners(root, &path.to_string_lossy().replace(MAIN_SEPARATOR, "/")))
+        .flatten()
+        .map(|owners| ConfigData {
+            author: Some(owners.join(", ")),
+            author_mail: None,
+            ..data.clone()
+        });
+    let data = owned.as_ref().unwrap_or(data);
+    // The `#` of the values are escaped until every pass is done.
+    let value = |text: &str| text.replace('#', ESCAPED_HASH);
+    let data = &ConfigData {
+        author: data.author.as_deref().map(value),
+        author_mail: data.author_mail.as_deref().map(value),
+        authors: data
+            .authors
+            .as_ref()
+            .map(|authors| authors.iter().map(|author| value(author)).collect()),
+        cp_holders: data.cp_holders.as_deref().map(value),
+        copyright_symbol: data.copyright_symbol.as_deref().map(value),
+        ..data.clone()
+    };
+
+    // Refreshed `after` lines are filled along with the template, then split
+    // from it.
+    let after = template.after.clone().unwrap_or_default();
+    let refresh_after = template.refresh_after.unwrap_or(false) && !after.is_empty();
+    let mut text = template.template.clone().unwrap_or_default();
+    if refresh_after {
+        text = format!("{}\n{}", text, after.join("\n"));
+    }
+    // Values holding tokens (a custom token holding `#author_name`) are filled
+    // by the following pass.
+    let fill = |text: &str| {
+        let mut res = text.replace(
+            "#copyright_notice",
+            data.copyright_notice.as_deref().unwrap_or_default(),
+        );
+
+        // `#author` is expanded first, its format holding other tokens.
+        let author = BTreeMap::from([(String::from("author"), render_author(data))]);
+        res = substitute_tokens(&res, &author);
+
+        res = res
+            .replace("#file_creation", &creation_date)
+            .replace("#date_now", &modification_date)
+            .replace("#file_modified_iso", &modification_iso)
+            .replace("#tz_offset", &tz_offset)
+            .replace(
+                "#file_relative_path",
+                &value(&relative_path(path, template)),
+            )
+            .replace(
+                "#project_name",
+                &value(&project.name.clone().unwrap_or_else(|| {
+                    Path::new(&project.root)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                })),
+            )
+            .replace("#authors", &render_authors(data))
+            .replace("#copyright_symbol", &copyright_symbol(data, template))
+            .replace("#author_name", data.author.as_deref().unwrap_or_default())
+            .replace("#cp_year", &year);
+        if res.contains("#last_author") {
+            let (name, mail) = last_author(&Path::new(root).join(path)).unwrap_or_else(|| {
+                (
+                    data.author.clone().unwrap_or_default(),
+                    data.author_mail.clone().unwrap_or_default(),
+                )
+            });
+            let mail = if mail.is_empty() {
+                mail
+            } else {
+                format!("<{}>", mail)
+            };
+            res = res
+                .replace("#last_author_mail", &value(&mail))
+                .replace("#last_author", &value(&name));
+        }
+        if res.contains("#contributors") {
+            let mut contributors = contributors(&Path::new(root).join(path))
+                .unwrap_or_else(|| vec![render_author(data)]);
+            if let Some(max) = data.max_contributors {
+                contributors.truncate(max);
+            }
+            res = res.replace("#contributors", &value(&contributors.join(", ")));
+        }
+        if let Some(mail) = data.author_mail.as_ref().filter(|f| !f.is_empty()) {
+            res = res.replace("#author_mail", format!("<{}>", mail).as_str());
+        } else {
+            res = res.replace("#author_mail", "");
+        }
+        if let Some(holders) = data.cp_holders.as_ref().filter(|f| !f.is_empty()) {
+            res = res.replace("#cp_holders", format!("<{}>", holders).as_str());
+        } else {
+            res = res.replace("#cp_holders", "");
+        }
+        if let Some(tokens) = &data.tokens {
+            res = substitute_tokens(&res, tokens);
+        }
+        res
+    };
+    let mut res = text;
+    for _ in 0..SUBSTITUTION_PASSES {
+        let filled = fill(&res);
+        if filled == res {
+            break;
+        }
+        res = filled;
+    }
+
+    // Templates (and data) written with CRLF line endings mustn’t leave carriage
+    // returns in the header, whatever the file’s line endings.
+    let res = res.replace(ESCAPED_HASH, "#").replace("\r\n", "\n");
+    let mut lines: Vec<&str> = res.split('\n').collect();
+    let after = if refresh_after {
+        let start = lines.len().saturating_sub(after.len());
+        lines.drain(start..).map(str::to_owned).collect()
+    } else {
+        after
+    };
+
+    let prefix = template.prefix.clone().unwrap_or_default();
+    let mut body: Vec<String> = template
+        .block_start
+        .clone()
+        .into_iter()
+        .chain(lines.into_iter().map(|s| prefix_line(&prefix, s)))
+        .chain(
+            template
+                .end_marker
+                .iter()
+                .map(|marker| format!("{}{}", prefix, marker)),
+        )
+        .collect();
+    if let Some(block_end) = &template.block_end {
+        match body.last_mut() {
+            Some(last) if !template.comment_close_on_own_line.unwrap_or(true) => {
+                *last = format!("{} {}", last, block_end);
+            }
+            _ => body.push(block_end.clone()),
+        }
+    }
+    Ok(template
+        .before
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .chain(body)
+        .chain(after)
+        .collect())
+}
+
+/// Gets the copyright symbol to put in the header, falling back to `(c)` if it
+/// isn’t plain ASCII while the data require it, or if the template’s encoding
+/// can’t represent it.
+///
+/// # Arguments
+/// * `data` - Data of the project.
+/// * `template` - Template of the header.
+///
+/// # Example
+/// ```ignore
+/// let symbol = copyright_symbol(&project.data.unwrap(), &lang_conf);
+/// ```
+fn copyright_symbol(data: &ConfigData, template: &Template) -> String {
+    let symbol = data.copyright_symbol.clone().unwrap_or(String::from("©"));
+    let encodable = template
+        .encoding
+        .as_ref()
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .is_none_or(|encoding| !encoding.encode(&symbol).2);
+    if (data.ascii_symbols.unwrap_or(false) || !encodable) && !symbol.is_ascii() {
+        String::from("(c)")
+    } else {
+        symbol
+    }
+}
+
+/// Gets the name and mail address of the last person who committed changes to
+/// a file, if git is available and the file is tracked.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file.
+///
+/// # Example
+/// ```ignore
+/// let (name, mail) = last_author(Path::new("/home/user/project/src/main.rs")).unwrap();
+/// ```
+fn last_author(path: &Path) -> Option<(String, String)> {
+    let output = Command::new("git")
+        .current_dir(path.parent()?)
+        .args(["log", "-1", "--format=%an%n%ae", "--"])
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let output = String::from_utf8(output.stdout).ok()?;
+    let (name, mail) = output.trim_end().split_once('\n')?;
+    Some((name.to_owned(), mail.to_owned()))
+}
+
+/// Gets the distinct authors of the commits changing a file, as `name <mail>`,
+/// the most active first, if git is available and the file is tracked. Names
+/// and mail addresses are canonicalized by the repository’s `.mailmap`.
+///
+/// # Arguments
+/// * `path` - Absolute path to the file.
+///
+/// # Example
+/// ```ignore
+/// let contributors = contributors(Path::new("/home/user/project/src/main.rs")).unwrap();
+/// ```
+fn contributors(path: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .current_dir(path.parent()?)
+        .args(["shortlog", "-sne", "HEAD", "--"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let output = String::from_utf8(output.stdout).ok()?;
+    // Every line is the number of commits, a tab, then the author.
+    let contributors: Vec<String> = output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(_, author)| author.trim().to_owned())
+        .collect();
+    (!contributors.is_empty()).then_some(contributors)
+}
+
+/// Renders the path of a file relative to its project’s root, with forward
+/// slashes unless the template disables them.
+///
+/// # Arguments
+/// * `path` - Path relative to the project’s root.
+/// * `template` - Template of the header.
+fn relative_path(path: &Path, template: &Template) -> String {
+    let path = path.to_str().unwrap_or("");
+    if template.forward_slashes.unwrap_or(true) && MAIN_SEPARATOR != '/' {
+        path.replace(MAIN_SEPARATOR, "/")
+    } else {
+        path.to_owned()
+    }
+}
+
+/// Format of the dates written in the headers (`#file_creation`, and
+/// `#date_now` followed by the time).
+///
+/// # Arguments
+/// * `template` - Template of the header.
+fn date_format(template: &Template) -> &'static str {
+    match template.date_style.unwrap_or_default() {
+        DateStyle::Long => "%A %d %B %Y",
+        DateStyle::Abbrev => "%a %d %b %Y",
+    }
+}
+
+/// Gets the modification date written in the existing header of some content,
+/// read from its `#file_modified_iso` line, or failing that its `#date_now`
+/// one. Nothing is returned if the header holds no such date, or if it can’t
+/// be parsed.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+/// * `locale` - Locale the dates are written in.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::{fill_template, written_modification_date}};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let template = Template {
+///     template: Some(String::from("File: #file_relative_path\nModified: #file_modified_iso")),
+///     ..template
+/// };
+/// let header = fill_template(&template, &project, path, &project.root, &dates)?;
+/// let content = [&header[..], &[String::from("fn main() {}")]].concat();
+/// let written = written_modification_date(&content, &header, &template, "fr").unwrap();
+/// assert_eq!(written.timestamp(), dates.modified.timestamp());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn written_modification_date(
+    content: &[String],
+    header: &[String],
+    template: &Template,
+    locale: &str,
+) -> Option<DateTime<Local>> {
+    let text = template.template.as_deref().unwrap_or_default();
+    let (token, line) = ["#file_modified_iso", "#date_now"]
+        .into_iter()
+        .find_map(|token| Some((token, text.lines().find(|line| line.contains(token))?)))?;
+    let (literal, rest) = line.split_once(token)?;
+    let marker = literal.split('#').next().unwrap_or_default().trim();
+    let suffix = rest.split('#').next().unwrap_or_default().trim();
+    if marker.is_empty() {
+        return None;
+    }
+
+    let index = header.iter().position(|line| line.contains(marker))?;
+    let line = strip_block_end(
+        content[..header_len(content, header, template)].get(index)?,
+        template,
+    );
+    let date = &line[line.find(marker)? + marker.len()..];
+    let date = match date.find(suffix) {
+        Some(end) if !suffix.is_empty() => &date[..end],
+        _ => date,
+    }
+    .trim();
+    if token == "#file_modified_iso" {
+        DateTime::parse_from_str(date, ISO_FORMAT)
+            .ok()
+            .map(|date| date.with_timezone(&Local))
+    } else {
+        let format = format!("{} @ %H:%M:%S", date_format(template));
+        parse_date(date, &format, locale).and_then(|date| date.and_local_timezone(Local).earliest())
+    }
+}
+
+/// Removes the closing string of a block comment from the end of a line, when
+/// the template puts it after the last line of the header.
+///
+/// # Arguments
+/// * `line` - Line of the file.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```ignore
+/// let line = strip_block_end(" * <<< auto-header end */", &lang_conf);
+/// ```
+fn strip_block_end<'a>(line: &'a str, template: &Template) -> &'a str {
+    let line = line.trim_end();
+    match &template.block_end {
+        Some(block_end) if !template.comment_close_on_own_line.unwrap_or(true) => line
+            .strip_suffix(block_end.as_str())
+            .map(str::trim_end)
+            .unwrap_or(line),
+        _ => line,
+    }
+}
+
+/// Pipes a generated header through the template’s `post_process` command, if
+/// any.
+///
+/// # Arguments
+/// * `header` - Generated header.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::header::post_process;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let header = post_process(header, &template)?;
+/// assert_eq!(header, ["// File: src/main.rs", "// Author: Jane Doe", ""]);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn post_process(header: Vec<String>, template: &Template) -> Result<Vec<String>> {
+    let Some(command) = &template.post_process else {
+        return Ok(sign_header(header));
+    };
+    let failure = |reason: String| AutoHeaderError::PostProcess {
+        command: command.clone(),
+        reason,
+    };
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| failure(err.to_string()))?;
+    let input = header.join("\n") + "\n";
+    let written = child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes());
+    // A command not reading its input is reported through its exit status.
+    match written {
+        Err(err) if err.kind() != ErrorKind::BrokenPipe => return Err(failure(err.to_string())),
+        _ => (),
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| failure(err.to_string()))?;
+    if !output.status.success() {
+        return Err(failure(output.status.to_string()));
+    }
+    let output = String::from_utf8(output.stdout)
+        .map_err(|_| failure(String::from("invalid UTF-8 output")))?;
+    Ok(sign_header(output.lines().map(str::to_owned).collect()))
+}
+
+/// Prepares a command to be run by the platform’s shell.
+///
+/// # Arguments
+/// * `command` - Command line.
+///
+/// # Example
+/// ```
+/// use auto_header::header::shell_command;
+///
+/// let status = shell_command("exit 3").status()?;
+/// assert_eq!(status.code(), Some(3));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn shell_command(command: &str) -> Command {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut shell = Command::new(shell);
+    shell.args([flag, command]);
+    shell
+}
+
+/// Token replaced by the signature of the header.
+const SIGNATURE: &str = "#signature";
+
+/// Computes the signature of a header: a hash of its lines (trailing spaces
+/// ignored), but the one holding the signature.
+///
+/// # Arguments
+/// * `lines` - Lines of the header.
+/// * `skip` - Index of the line holding the signature.
+fn signature(lines: &[String], skip: usize) -> String {
+    // FNV-1a, stable across versions and platforms unlike the standard hasher.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (_, line) in lines.iter().enumerate().filter(|(index, _)| *index != skip) {
+        for byte in line.trim_end().bytes().chain(*b"\n") {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Replaces the `#signature` token of a generated header by its signature.
+///
+/// # Arguments
+/// * `header` - Generated header.
+fn sign_header(mut header: Vec<String>) -> Vec<String> {
+    if let Some(index) = header.iter().position(|line| line.contains(SIGNATURE)) {
+        let signature = signature(&header, index);
+        header[index] = header[index].replace(SIGNATURE, &signature);
+    }
+    header
+}
+
+/// Locates the signature written in the existing header of some content: the
+/// index of its line, its position in the line, and its value.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+fn find_signature(
+    content: &[String],
+    header: &[String],
+    template: &Template,
+) -> Option<(usize, usize, String)> {
+    let marker = template.signature_marker()?;
+    let index = header.iter().position(|line| line.contains(&marker))?;
+    let line = content[..header_len(content, header, template)].get(index)?;
+    let after = line.find(&marker)? + marker.len();
+    let start = after + line[after..].len() - line[after..].trim_start().len();
+    let stored = line[start..]
+        .chars()
+        .take_while(char::is_ascii_hexdigit)
+        .collect();
+    Some((index, start, stored))
+}
+
+/// Checks whether the existing header of some content was edited by hand
+/// since it was written: its signature doesn’t match its lines anymore.
+/// Headers without signature are never considered edited.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::{fill_template, header_edited, post_process}};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let template = Template {
+///     template: Some(String::from("File: #file_relative_path\nSignature: #signature")),
+///     ..template
+/// };
+/// let header = post_process(fill_template(&template, &project, path, &project.root, &dates)?, &template)?;
+/// assert!(!header_edited(&header, &header, &template));
+/// let mut content = header.clone();
+/// content[0] = String::from("// File: src/lib.rs");
+/// assert!(header_edited(&content, &header, &template));
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn header_edited(content: &[String], header: &[String], template: &Template) -> bool {
+    find_signature(content, header, template).is_some_and(|(index, _, stored)| {
+        stored != signature(&content[..header_len(content, header, template)], index)
+    })
+}
+
+/// Recomputes the signature of the existing header of some content, once it
+/// was modified (updated lines, renamed author).
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::{fill_template, header_edited, post_process, resign_header}};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let template = Template {
+///     template: Some(String::from("File: #file_relative_path\nSignature: #signature")),
+///     ..template
+/// };
+/// let header = post_process(fill_template(&template, &project, path, &project.root, &dates)?, &template)?;
+/// let mut content = header.clone();
+/// content[0] = String::from("// File: src/lib.rs");
+/// let content = resign_header(&content, &header, &template);
+/// assert!(!header_edited(&content, &header, &template));
+/// assert_ne!(content[1], header[1]);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn resign_header(content: &[String], header: &[String], template: &Template) -> Vec<String> {
+    let mut content = content.to_vec();
+    if let Some((index, start, stored)) = find_signature(&content, header, template) {
+        let signature = signature(&content[..header_len(&content, header, template)], index);
+        content[index].replace_range(start..start + stored.len(), &signature);
+    }
+    content
+}
+
+/// Replaces the custom `#tokens` of a text by their value. Only whole tokens
+/// are replaced (`#gen` doesn’t match the beginning of `#gen_tool`), and
+/// unknown tokens are left untouched.
+///
+/// # Arguments
+/// * `text` - Text in which to replace the tokens.
+/// * `tokens` - Values of the tokens, by name (without the leading `#`).
+///
+/// # Example
+/// ```
+/// use auto_header::header::substitute_tokens;
+/// use std::collections::BTreeMap;
+///
+/// let tokens = BTreeMap::from([(String::from("gen_tool"), String::from("protoc"))]);
+/// assert_eq!(substitute_tokens("Generated by #gen_tool", &tokens), "Generated by protoc");
+/// ```
+pub fn substitute_tokens(text: &str, tokens: &BTreeMap<String, String>) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('#') {
+        res.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        match tokens.get(&after[..len]) {
+            Some(value) if len > 0 => res.push_str(value),
+            _ => {
+                res.push('#');
+                res.push_str(&after[..len]);
+            }
+        }
+        rest = &after[len..];
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Gets the names of the `#tokens` of a text (without the leading `#`), in
+/// order of appearance and without duplicates.
+///
+/// # Arguments
+/// * `text` - Text holding the tokens.
+///
+/// # Example
+/// ```
+/// use auto_header::header::template_tokens;
+///
+/// assert_eq!(template_tokens("#author_name (#cp_year)"), ["author_name", "cp_year"]);
+/// ```
+pub fn template_tokens(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for (start, _) in text.match_indices('#') {
+        let after = &text[start + 1..];
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        if len > 0 && !tokens.iter().any(|token| token == &after[..len]) {
+            tokens.push(after[..len].to_owned());
+        }
+    }
+    tokens
+}
+
+/// Tokens built in the application, with their description.
+pub const BUILTIN_TOKENS: [(&str, &str); 18] = [
+    (
+        "author",
+        "Main author, rendered with author_format (name and mail by default)",
+    ),
+    ("author_name", "Name of the main author"),
+    ("author_mail", "Mail address of the main author, between <>"),
+    ("authors", "Main author and the other ones"),
+    (
+        "last_author",
+        "Name of the last git author of the file (main author if unknown)",
+    ),
+    (
+        "last_author_mail",
+        "Mail address of the last git author of the file, between <>",
+    ),
+    (
+        "contributors",
+        "Authors of the file’s commits, most active first (main author if unknown)",
+    ),
+    ("file_creation", "Creation date of the file"),
+    ("date_now", "Modification date of the file, with its time"),
+    (
+        "file_modified_iso",
+        "Modification date of the file, in ISO 8601 format",
+    ),
+    (
+        "tz_offset",
+        "Offset of the time zone of the dates (+02:00, or Z for UTC)",
+    ),
+    (
+        "file_relative_path",
+        "Path of the file relative to the project’s root",
+    ),
+    (
+        "project_name",
+        "Name of the project (its root directory’s by default)",
+    ),
+    ("copyright_notice", "Copyright notice"),
+    ("copyright_symbol", "Copyright symbol (© by default)"),
+    ("cp_year", "Current year"),
+    ("cp_holders", "Copyright holders, between <>"),
+    (
+        "signature",
+        "Signature of the header, detecting edits by hand",
+    ),
+];
+
+/// Renders a single token as it would be in the header of a file. Nothing is
+/// returned for `#signature`, which depends on the whole header.
+///
+/// # Arguments
+/// * `token` - Name of the token (without the leading `#`).
+/// * `template` - Template of the header (merged).
+/// * `project` - Project the file belongs to (resolved).
+/// * `path` - Path of the file.
+/// * `dates` - Dates of the file.
+///
+/// # Example
+/// ```
+/// use auto_header::header::render_token;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let rendered = render_token("file_relative_path", &template, &project, path, &dates)?;
+/// assert_eq!(rendered.as_deref(), Some("src/main.rs"));
+/// let year = render_token("cp_year", &template, &project, path, &dates)?;
+/// assert_eq!(year, Some(dates.created.format("%Y").to_string()));
+/// assert_eq!(render_token("signature", &template, &project, path, &dates)?, None);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn render_token(
+    token: &str,
+    template: &Template,
+    project: &Project,
+    path: &str,
+    dates: &FileDates,
+) -> Result<Option<String>> {
+    if SIGNATURE.strip_prefix('#') == Some(token) {
+        return Ok(None);
+    }
+    let bare = Template {
+        template: Some(format!("#{}", token)),
+        prefix: None,
+        before: None,
+        after: None,
+        end_marker: None,
+        block_start: None,
+        block_end: None,
+        ..template.clone()
+    };
+    Ok(Some(
+        fill_template(&bare, project, path, &project.root, dates)?.join("\n"),
+    ))
+}
+
+/// Renders the main author with the configured `author_format`, in which
+/// `#author_name` and `#author_mail` are replaced by the raw name and mail
+/// address. Without a format, the mail address follows the name between `<>`.
+///
+/// # Arguments
+/// * `data` - Data of the project.
+///
+/// # Example
+/// ```ignore
+/// let data = project.data.clone().unwrap();
+/// let author = render_author(data);
+/// ```
+fn render_author(data: &ConfigData) -> String {
+    let name = data.author.clone().unwrap_or_default();
+    let mail = data.author_mail.clone().unwrap_or_default();
+    match &data.author_format {
+        Some(format) => format
+            .replace("#author_name", &name)
+            .replace("#author_mail", &mail)
+            .trim()
+            .to_owned(),
+        None if mail.is_empty() => name,
+        None => format!("{} <{}>", name, mail),
+    }
+}
+
+/// Renders the list of authors (main author first, then the other ones),
+/// sorted and deduplicated according to the configuration.
+///
+/// # Arguments
+/// * `data` - Data of the project.
+///
+/// # Example
+/// ```ignore
+/// let data = project.data.clone().unwrap();
+/// let authors = render_authors(&data);
+/// ```
+fn render_authors(data: &ConfigData) -> String {
+    let mut authors: Vec<String> = data
+        .author
+        .iter()
+        .chain(data.authors.iter().flatten())
+        .map(|author| author.trim().to_owned())
+        .filter(|author| !author.is_empty())
+        .collect();
+    if data.authors_sort.unwrap_or_default() {
+        authors.sort_by_key(|author| author.to_lowercase());
+    }
+    if data.authors_dedupe.unwrap_or_default() {
+        let mut seen = HashSet::new();
+        authors.retain(|author| seen.insert(author.clone()));
+    }
+    authors.join(", ")
+}
+
+/// Prefixes a line of the header. Empty lines (blank lines of a license for
+/// example) only get the trimmed prefix, to avoid trailing whitespace.
+///
+/// # Arguments
+/// * `prefix` - Prefix of the template.
+/// * `line` - Line to prefix.
+///
+/// # Example
+/// ```
+/// use auto_header::header::prefix_line;
+///
+/// assert_eq!(prefix_line("// ", "File: main.rs"), "// File: main.rs");
+/// assert_eq!(prefix_line("// ", ""), "//");
+/// ```
+pub fn prefix_line(prefix: &str, line: &str) -> String {
+    if line.is_empty() {
+        prefix.trim_end().to_owned()
+    } else {
+        format!("{}{}", prefix, line)
+    }
+}
+
+/// Finds the index of the line holding the header’s end marker, if the template
+/// defines one and it is present in the content.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::find_end_marker};
+///
+/// let template = Template {
+///     prefix: Some(String::from("// ")),
+///     end_marker: Some(String::from("<<< auto-header end")),
+///     ..Default::default()
+/// };
+/// let content = vec![String::from("// File: main.rs"), String::from("// <<< auto-header end")];
+/// assert_eq!(find_end_marker(&content, &template), Some(1));
+/// assert_eq!(find_end_marker(&content[..1], &template), None);
+/// ```
+pub fn find_end_marker(content: &[String], template: &Template) -> Option<usize> {
+    let marker = template.end_marker.as_ref()?;
+    let line = format!("{}{}", template.prefix.clone().unwrap_or_default(), marker);
+    content
+        .iter()
+        .position(|c| strip_block_end(c, template).trim_start() == line.trim())
+}
+
+/// Prefixes of the line comments known to the application, used to recognize
+/// headers written with another comment syntax than the template’s.
+const COMMENT_PREFIXES: [&str; 5] = ["//", "#", "--", ";", "%"];
+
+/// Gets variants of a template using the other known line comment syntaxes,
+/// to recognize headers written for another language (a file that was
+/// reclassified, or imported from another project).
+///
+/// # Arguments
+/// * `template` - Template of the header.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::foreign_templates};
+///
+/// let template = Template {
+///     prefix: Some(String::from("// ")),
+///     ..Default::default()
+/// };
+/// let prefixes: Vec<_> = foreign_templates(&template)
+///     .into_iter()
+///     .filter_map(|foreign| foreign.prefix)
+///     .collect();
+/// assert_eq!(prefixes, ["# ", "-- ", "; ", "% "]);
+/// ```
+pub fn foreign_templates(template: &Template) -> Vec<Template> {
+    let prefix = template.prefix.clone().unwrap_or_default();
+    COMMENT_PREFIXES
+        .iter()
+        .filter(|foreign| template.block_start.is_some() || prefix.trim() != **foreign)
+        .map(|foreign| Template {
+            prefix: Some(format!("{} ", foreign)),
+            block_start: None,
+            block_end: None,
+            ..template.clone()
+        })
+        .collect()
+}
+
+/// Gets the number of lines of an existing header at the beginning of some
+/// content.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::header::{header_len, with_header};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let content = with_header(&[String::from("fn main() {}"), String::new()], &header);
+/// let len = header_len(&content, &header, &template);
+/// assert_eq!(&content[len..], ["fn main() {}", ""]);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn header_len(content: &[String], header: &[String], template: &Template) -> usize {
+    match find_end_marker(content, template) {
+        Some(end) => {
+            let after = template.after.as_ref().map_or(0, Vec::len);
+            (end + 1 + after).min(content.len())
+        }
+        None => header.len().min(content.len()),
+    }
+}
+
+/// Replaces a string (an author’s name or mail address) in the header region of
+/// some content, leaving the rest of it untouched.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+/// * `from` - String to replace.
+/// * `to` - Replacement.
+///
+/// # Example
+/// ```
+/// use auto_header::header::{rename_in_header, with_header};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let content = with_header(&[String::from("// Jane Doe wrote this.")], &header);
+/// let content = rename_in_header(&content, &header, &template, "Jane Doe", "Jane Smith");
+/// assert_eq!(content[1], "// Author: Jane Smith");
+/// assert_eq!(content[3], "// Jane Doe wrote this.");
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn rename_in_header(
+    content: &[String],
+    header: &[String],
+    template: &Template,
+    from: &str,
+    to: &str,
+) -> Vec<String> {
+    let len = header_len(content, header, template);
+    content
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i < len {
+                line.replace(from, to)
+            } else {
+                line.clone()
+            }
+        })
+        .collect()
+}
+
+/// Gets the index of the first line of the header written at the end of some
+/// content (`position = "bottom"`), if there’s one. The final line feed of the
+/// content follows the header.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::header::footer_start;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let content = [&[String::from("fn main() {}")], &header[..], &[String::new()]].concat();
+/// assert_eq!(footer_start(&content, &header, &template), Some(1));
+/// assert_eq!(footer_start(&content[..2], &header, &template), None);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn footer_start(content: &[String], header: &[String], template: &Template) -> Option<usize> {
+    let end = content.len() - usize::from(content.last().is_some_and(String::is_empty));
+    let start = end.checked_sub(header.len())?;
+    header_exists(&content[start..end], header, template).then_some(start)
+}
+
+/// Check if a matching header is found in the given content.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::header::{header_exists, with_header};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let body = vec![String::from("fn main() {}"), String::new()];
+/// assert!(header_exists(&with_header(&body, &header), &header, &template));
+/// assert!(!header_exists(&body, &header, &template));
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn header_exists(content: &[String], header: &[String], template: &Template) -> bool {
+    if template.end_marker.is_some() {
+        return find_end_marker(content, template).is_some();
+    }
+    if content.len() < required_len(header, template) {
+        return false;
+    }
+    header_lines_match(content, header, template)
+}
+
+/// Gets the number of lines some content must have to hold a header. A file
+/// holding only its header may lack the blank lines ending it (`after`), there
+/// being no body to separate from it.
+///
+/// # Arguments
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+fn required_len(header: &[String], template: &Template) -> usize {
+    let blank_after = template.after.iter().flatten().rev();
+    header.len()
+        - blank_after
+            .take_while(|line| line.trim().is_empty())
+            .count()
+}
+
+/// Check if the content is shorter than the header, but is the beginning of a
+/// matching header.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::header::header_damaged;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// assert!(header_damaged(&header[..1], &header, &template));
+/// assert!(!header_damaged(&[String::from("fn main() {}")], &header, &template));
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn header_damaged(content: &[String], header: &[String], template: &Template) -> bool {
+    if template.end_marker.is_some()
+        || content.len() >= header.len()
+        || content.first().is_none_or(|line| line.is_empty())
+    {
+        return false;
+    }
+    let content = match content.split_last() {
+        Some((last, rest)) if last.is_empty() => rest,
+        _ => content,
+    };
+    // A file holding only the template’s `before` lines (a shebang) has no header yet.
+    if content.len() <= existing_before_len(content, template) {
+        return false;
+    }
+    header_lines_match(content, header, template)
+}
+
+/// Reads the lines of a file.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+///
+/// # Example
+/// ```ignore
+/// let content = read_lines("./src/main.rs")?;
+/// ```
+pub fn read_lines(path: &str) -> Result<Vec<String>> {
+    let content = fs::read(path).map_err(AutoHeaderError::read(path))?;
+    let content =
+        str::from_utf8(&content).map_err(|_| AutoHeaderError::InvalidUtf8(path.to_owned()))?;
+    Ok(content.split('\n').map(|s| s.to_owned()).collect())
+}
+
+/// Gets the character encoding of the files a template applies to (UTF-8 by
+/// default).
+///
+/// # Arguments
+/// * `template` - Template of the header.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::template_encoding};
+///
+/// assert_eq!(template_encoding(&Template::default())?.name(), "UTF-8");
+/// let template = Template {
+///     encoding: Some(String::from("latin1")),
+///     ..Default::default()
+/// };
+/// assert_eq!(template_encoding(&template)?.name(), "windows-1252");
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn template_encoding(template: &Template) -> Result<&'static Encoding> {
+    match &template.encoding {
+        Some(label) => Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| AutoHeaderError::UnknownEncoding(label.clone())),
+        None => Ok(UTF_8),
+    }
+}
+
+/// Decodes the content of a file into lines.
+///
+/// # Arguments
+/// * `path` - Path to the file (used for error reporting).
+/// * `bytes` - Content of the file.
+/// * `encoding` - Character encoding of the file.
+///
+/// # Example
+/// ```
+/// use auto_header::header::decode_lines;
+/// use encoding_rs::{UTF_8, WINDOWS_1252};
+///
+/// let content = decode_lines("menu.txt", b"caf\xe9\n", WINDOWS_1252)?;
+/// assert_eq!(content, ["café", ""]);
+/// assert!(decode_lines("menu.txt", b"caf\xe9\n", UTF_8).is_err());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn decode_lines(path: &str, bytes: &[u8], encoding: &'static Encoding) -> Result<Vec<String>> {
+    let content = encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .ok_or_else(|| invalid_encoding(path, encoding))?;
+    Ok(content.split('\n').map(|s| s.to_owned()).collect())
+}
+
+/// Encodes lines into the content of a file.
+///
+/// # Arguments
+/// * `path` - Path to the file (used for error reporting).
+/// * `lines` - Lines of the file.
+/// * `encoding` - Character encoding of the file.
+///
+/// # Example
+/// ```
+/// use auto_header::header::encode_lines;
+/// use encoding_rs::WINDOWS_1252;
+///
+/// let bytes = encode_lines("menu.txt", &[String::from("café"), String::new()], WINDOWS_1252)?;
+/// assert_eq!(bytes, b"caf\xe9\n");
+/// assert!(encode_lines("menu.txt", &[String::from("☕")], WINDOWS_1252).is_err());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn encode_lines(path: &str, lines: &[String], encoding: &'static Encoding) -> Result<Vec<u8>> {
+    let content = lines.join("\n");
+    let (bytes, _, unmappable) = encoding.encode(&content);
+    if unmappable {
+        return Err(invalid_encoding(path, encoding));
+    }
+    Ok(bytes.into_owned())
+}
+
+/// Error for content that can’t be represented in a character encoding.
+fn invalid_encoding(path: &str, encoding: &'static Encoding) -> AutoHeaderError {
+    if encoding == UTF_8 {
+        AutoHeaderError::InvalidUtf8(path.to_owned())
+    } else {
+        AutoHeaderError::InvalidEncoding {
+            path: path.to_owned(),
+            encoding: encoding.name().to_owned(),
+        }
+    }
+}
+
+/// Gets the number of lines to read at the beginning of a file to find its
+/// header, checking the template’s `header_lines` hint against the actual
+/// header. Nothing is returned if the whole file must be read (the header is
+/// located by its end marker, without hint).
+///
+/// # Arguments
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::header_line_count};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// assert_eq!(header_line_count(&header, &template)?, Some(3));
+/// let template = Template { header_lines: Some(2), ..template };
+/// assert!(header_line_count(&header, &template).is_err());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn header_line_count(header: &[String], template: &Template) -> Result<Option<usize>> {
+    match template.header_lines {
+        Some(hint) if hint != header.len() => Err(AutoHeaderError::HeaderLines {
+            hint,
+            actual: header.len(),
+        }),
+        Some(hint) => Ok(Some(hint)),
+        None if template.end_marker.is_some() => Ok(None),
+        None => Ok(Some(header.len())),
+    }
+}
+
+/// Reads the first lines of a file, without loading the rest of it.
+///
+/// Returns the lines, and the position of the rest of the file if there’s
+/// more to it.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `count` - Number of lines to read.
+///
+/// # Example
+/// ```ignore
+/// let (head, rest) = read_head(&args.path, header.len())?;
+/// ```
+pub fn read_head(path: &str, count: usize) -> Result<(Vec<String>, Option<u64>)> {
+    let file = fs::File::open(path).map_err(AutoHeaderError::read(path))?;
+    let mut reader = BufReader::new(file);
+    let mut content = Vec::new();
+    let mut complete_lines = 0;
+    while complete_lines < count {
+        let read = reader
+            .read_until(b'\n', &mut content)
+            .map_err(AutoHeaderError::read(path))?;
+        if read == 0 || content.last() != Some(&b'\n') {
+            break;
+        }
+        complete_lines += 1;
+    }
+    let text =
+        str::from_utf8(&content).map_err(|_| AutoHeaderError::InvalidUtf8(path.to_owned()))?;
+    let mut lines: Vec<String> = text.split('\n').map(|s| s.to_owned()).collect();
+    if complete_lines < count {
+        return Ok((lines, None));
+    }
+    // The empty string after the last line read is the beginning of the rest.
+    lines.pop();
+    Ok((lines, Some(content.len() as u64)))
+}
+
+/// Compares the lines of a file with those of a header, ignoring the lines
+/// that are expected to change (creation date and tracked lines).
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+pub fn header_lines_match(content: &[String], header: &[String], template: &Template) -> bool {
+    let prefix = template.prefix.clone().unwrap_or_default();
+    let tracked = template.tracked_lines();
+    let creation = template.creation_line_marker();
+    let mark = prefix.trim();
+    let refreshed = refreshed_after_start(header, template);
+    content
+        .iter()
+        .zip(header.iter())
+        .enumerate()
+        .all(|(index, (ci, hi))| {
+            index >= refreshed
+            || hi.trim() == ci.trim()
+            // The same text behind a prefix spaced differently (`//File`).
+            || (!mark.is_empty()
+                && ci.trim_start().starts_with(mark)
+                && hi.trim_start().starts_with(mark)
+                && unprefixed(ci, &prefix).trim_end() == unprefixed(hi, &prefix).trim_end())
+            || creation
+                .as_ref()
+                .is_some_and(|marker| ci.contains(marker.as_str()))
+            || tracked
+                .iter()
+                .any(|t| unprefixed(ci, &prefix).starts_with(t.as_str()))
+        })
+}
+
+/// Gets the index of the first `after` line of a generated header if they’re
+/// refreshed (`refresh_after`), or its length otherwise.
+///
+/// # Arguments
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+fn refreshed_after_start(header: &[String], template: &Template) -> usize {
+    match template.refresh_after {
+        Some(true) => header
+            .len()
+            .saturating_sub(template.after.as_ref().map_or(0, Vec::len)),
+        _ => header.len(),
+    }
+}
+
+/// Writes the new content of a file by only rewriting the bytes that changed:
+/// the beginning of the file it shares with the new content is left untouched,
+/// and so is its end if the length doesn’t change (tracked lines of the same
+/// length, such as dates).
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `content` - New content of the file.
+///
+/// # Example
+/// ```ignore
+/// let updated = updated_content(&args.path, &content, &header, &lang_conf)?;
+/// splice_file(&args.path, updated.join("\n").as_bytes())?;
+/// ```
+pub fn splice_file(path: &str, content: &[u8]) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(AutoHeaderError::read(path))?;
+    let mut existing = Vec::new();
+    file.read_to_end(&mut existing)
+        .map_err(AutoHeaderError::read(path))?;
+    let start = existing
+        .iter()
+        .zip(content)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let end = if existing.len() == content.len() {
+        content.len()
+            - existing[start..]
+                .iter()
+                .rev()
+                .zip(content[start..].iter().rev())
+                .take_while(|(old, new)| old == new)
+                .count()
+    } else {
+        content.len()
+    };
+    file.seek(SeekFrom::Start(start as u64))
+        .and_then(|_| file.write_all(&content[start..end]))
+        .and_then(|_| file.set_len(content.len() as u64))
+        .map_err(AutoHeaderError::write(path))
+}
+
+/// Gets the content of a file once the tracked lines of its header are updated.
+///
+/// # Arguments
+/// * `path` - Path to the file (used for error reporting).
+/// * `content` - Lines of the file.
+/// * `header` - New generated header.
+/// * `template` - Template the header was generated with.
+///
+/// # Example
+/// ```
+/// use auto_header::header::updated_content;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let content = vec![
+///     String::from("// File: src/old.rs"),
+///     String::from("// Author: Jane Doe"),
+///     String::new(),
+///     String::from("fn main() {}"),
+/// ];
+/// let updated = updated_content(path, &content, &header, &template)?;
+/// assert_eq!(updated[0], "// File: src/main.rs");
+/// assert_eq!(updated[1..], content[1..]);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn updated_content(
+    path: &str,
+    content: &[String],
+    header: &[String],
+    template: &Template,
+) -> Result<Vec<String>> {
+    let mut content = content.to_vec();
+    let tracked = template.tracked_lines();
+    let prefix = template.prefix.clone().unwrap_or_default();
+    if let Some(end) = find_end_marker(&content, template) {
+        // The header region is known: update tracked lines wherever they are in it.
+        for h in header {
+            let Some(tracked) = tracked
+                .iter()
+                .find(|s| unprefixed(h, &prefix).starts_with(s.as_str()))
+            else {
+                continue;
+            };
+            if let Some(line) = content[..end]
+                .iter_mut()
+                .find(|c| unprefixed(c, &prefix).starts_with(tracked.as_str()))
+            {
+                *line = reindented(h, line);
+            }
+        }
+        let refreshed = &header[refreshed_after_start(header, template)..];
+        for (line, h) in content.iter_mut().skip(end + 1).zip(refreshed) {
+            *line = h.clone();
+        }
+        return Ok(content);
+    }
+    if content.len() < required_len(header, template) {
+        return Err(AutoHeaderError::DamagedHeader(path.to_owned()));
+    }
+    // Only the lines the content holds are updated (a file holding exactly its
+    // header isn’t extended).
+    let refreshed = refreshed_after_start(header, template);
+    header
+        .iter()
+        .zip(content.iter_mut())
+        .enumerate()
+        .for_each(|(index, (h, c))| {
+            if index >= refreshed {
+                *c = h.clone();
+            } else if tracked
+                .iter()
+                .any(|s| unprefixed(h, &prefix).starts_with(s.as_str()))
+            {
+                *c = reindented(h, c);
+            }
+        });
+    Ok(content)
+}
+
+/// Removes the comment prefix of a header line, whatever its indentation.
+///
+/// # Arguments
+/// * `line` - Line of a header.
+/// * `prefix` - Comment prefix of the template.
+fn unprefixed(line: &str, prefix: &str) -> String {
+    let line = line.trim_start();
+    let text = line.strip_prefix(prefix.trim()).unwrap_or(line);
+    // Whatever separates the prefix from the text (`//File`, `//\tFile`).
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) if c.is_whitespace() => chars.as_str().to_owned(),
+        _ => text.to_owned(),
+    }
+}
+
+/// Rewrites the comment prefix of every line of an existing header to be
+/// exactly the template’s, keeping the text of the lines (headers written with
+/// `//` while the prefix is now `// ` for example). The template’s `before`
+/// lines and block delimiters are left untouched.
+///
+/// # Arguments
+/// * `content` - Lines of the file, starting with the header.
+/// * `header` - Header generated.
+/// * `template` - Template the header was generated from.
+///
+/// # Example
+/// ```
+/// use auto_header::header::normalize_prefixes;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// # let path = "/home/user/project/src/main.rs";
+/// # let (project, template) = auto_header::resolve(&config, path)?;
+/// # let dates = auto_header::header::FileDates::now();
+/// # let header = auto_header::header::fill_template(&template, &project, path, &project.root, &dates)?;
+/// let content = vec![
+///     String::from("//File: src/main.rs"),
+///     String::from("//\tAuthor: Jane Doe"),
+///     String::new(),
+///     String::from("fn main() {}"),
+/// ];
+/// let content = normalize_prefixes(&content, &header, &template);
+/// assert_eq!(content[..2], header[..2]);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn normalize_prefixes(
+    content: &[String],
+    header: &[String],
+    template: &Template,
+) -> Vec<String> {
+    let prefix = template.prefix.clone().unwrap_or_default();
+    let mark = prefix.trim();
+    let delimiters = [&template.block_start, &template.block_end];
+    let before = template.before.as_ref().map_or(0, Vec::len);
+    let len = header_len(content, header, template);
+    let mut content = content.to_vec();
+    if mark.is_empty() {
+        return content;
+    }
+    for (line, generated) in content[..len].iter_mut().zip(header).skip(before) {
+        let delimiter = delimiters
+            .iter()
+            .any(|d| d.as_ref().is_some_and(|d| d.trim() == generated.trim()));
+        if !delimiter && line.trim_start().starts_with(mark) {
+            *line = reindented(&prefix_line(&prefix, &unprefixed(line, &prefix)), line);
+        }
+    }
+    content
+}
+
+/// Gets an updated header line, indented as the line it replaces (for headers
+/// living in an indented context).
+///
+/// # Arguments
+/// * `new` - Generated line.
+/// * `old` - Line of the existing header.
+fn reindented(new: &str, old: &str) -> String {
+    let indent = &old[..old.len() - old.trim_start().len()];
+    format!("{}{}", indent, new.trim_start())
+}
+
+/// Sets the modification date of a file, so that it matches the one written in
+/// its header.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `date` - New modification date.
+///
+/// # Example
+/// ```ignore
+/// let dates = FileDates::from_file(&args.path)?.touched();
+/// set_modified(&args.path, &dates.modified)?;
+/// ```
+pub fn set_modified(path: &str, date: &DateTime<Local>) -> Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified((*date).into()))
+        .map_err(AutoHeaderError::write(path))
+}
+
+/// Writes a new header to the file, after its shebang (and the encoding
+/// declaration following it) if it has one and the header doesn’t.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `header` - New generated header.
+///
+/// # Example
+/// ```ignore
+/// # let args = Args::parse();
+/// # let config = toml::from_str(fs::read_to_string(args.config)?.as_str()).unwrap();
+/// # let project = find_project(&config, &args.path)?.unwrap().resolve(&config);
+/// # let lang_conf = get_language_config(&config, &language).unwrap().merge(&config.default);
+/// let header = fill_template(&lang_conf, &project, &args.path, &project.root, &dates)?;
+/// write_header(&args.path, &header)?;
+/// ```
+pub fn write_header(path: &str, header: &[String]) -> Result<()> {
+    let content = read_lines(path)?;
+    // The shebang stays first, unless the header brings its own.
+    let shebang = match header.first() {
+        Some(line) if line.starts_with("#!") => 0,
+        _ => shebang_len(&content),
+    };
+    let content = [
+        &content[..shebang],
+        &with_header(&content[shebang..], header)[..],
+    ]
+    .concat();
+    fs::write(path, content.join("\n")).map_err(AutoHeaderError::write(path))
+}
+
+/// Gets the number of leading lines of some content that must stay before
+/// the header: the template’s `protect_leading_lines` first lines (or the
+/// shebang and its encoding declaration, if more), followed by the lines
+/// matching its `insert_after` regular expression (the header goes right after
+/// the last of them, blank lines between them being part of the leading
+/// lines).
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `template` - Template of the header.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::prelude_len};
+///
+/// let content = vec![String::from("#!/bin/sh"), String::from("echo"), String::new()];
+/// assert_eq!(prelude_len(&content, &Template::default())?, 1);
+/// let template = Template {
+///     insert_after: Some(String::from("^echo")),
+///     ..Default::default()
+/// };
+/// assert_eq!(prelude_len(&content, &template)?, 2);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn prelude_len(content: &[String], template: &Template) -> Result<usize> {
+    // The empty string following the final line feed is never protected.
+    let lines = content.len() - usize::from(content.last().is_some_and(String::is_empty));
+    let protected = template
+        .protect_leading_lines
+        .unwrap_or(0)
+        .max(kept_shebang_len(content, template))
+        .min(lines);
+    let Some(pattern) = &template.insert_after else {
+        return Ok(protected);
+    };
+    let anchor = Regex::new(pattern).map_err(|err| AutoHeaderError::InvalidRegex {
+        pattern: pattern.clone(),
+        reason: err.to_string(),
+    })?;
+    let mut len = protected;
+    for (index, line) in content.iter().enumerate().skip(protected) {
+        if anchor.is_match(line) {
+            len = index + 1;
+        } else if !line.trim().is_empty() {
+            break;
+        }
+    }
+    Ok(len)
+}
+
+/// Gets the number of lines at the beginning of some content holding its
+/// shebang and the encoding declaration following it (PEP 263), which must
+/// stay first for the file to be run.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+fn shebang_len(content: &[String]) -> usize {
+    if !content.first().is_some_and(|line| line.starts_with("#!")) {
+        return 0;
+    }
+    let coding = |line: &String| {
+        line.trim_start().starts_with('#') && (line.contains("coding:") || line.contains("coding="))
+    };
+    1 + usize::from(content.get(1).is_some_and(coding))
+}
+
+/// Gets the number of lines of the shebang kept before the header, if
+/// `keep_shebang` is on and the template’s `before` lines don’t write a
+/// shebang themselves (see `own_shebang_template` for files having one).
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `template` - Template of the header.
+fn kept_shebang_len(content: &[String], template: &Template) -> usize {
+    let before = template.before.as_deref().unwrap_or_default();
+    if !template.keep_shebang.unwrap_or(true)
+        || before.first().is_some_and(|line| line.starts_with("#!"))
+    {
+        return 0;
+    }
+    shebang_len(content)
+}
+
+/// Gets the template to use for some content starting with its own shebang,
+/// when the template’s `before` lines write one too: the file’s shebang (the
+/// interpreter of that file) is kept first, and the template’s is dropped,
+/// along with its encoding declaration if the file has one. Nothing is
+/// returned when the template applies as is.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `template` - Template of the header.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::own_shebang_template};
+///
+/// let template = Template {
+///     before: Some(vec![String::from("#!/usr/bin/env python3")]),
+///     ..Default::default()
+/// };
+/// let content = vec![String::from("#!/usr/bin/python3"), String::from("print()")];
+/// let adjusted = own_shebang_template(&content, &template).unwrap();
+/// assert_eq!(adjusted.before, Some(vec![]));
+/// assert!(own_shebang_template(&content[1..], &template).is_none());
+/// ```
+pub fn own_shebang_template(content: &[String], template: &Template) -> Option<Template> {
+    let before = template.before.as_deref().unwrap_or_default();
+    let dropped = shebang_len(content).min(shebang_len(before));
+    if !template.keep_shebang.unwrap_or(true) || dropped == 0 {
+        return None;
+    }
+    Some(Template {
+        before: Some(before[dropped..].to_vec()),
+        ..template.clone()
+    })
+}
+
+/// Gets the number of lines at the beginning of some content that are already
+/// the template’s `before` lines (a shebang for example), and thus must not be
+/// emitted again when the header is created.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `template` - Template of the header.
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Template, header::existing_before_len};
+///
+/// let template = Template {
+///     before: Some(vec![String::from("#!/bin/sh")]),
+///     ..Default::default()
+/// };
+/// let content = vec![String::from("#!/bin/sh"), String::from("echo"), String::new()];
+/// assert_eq!(existing_before_len(&content, &template), 1);
+/// assert_eq!(existing_before_len(&content[1..], &template), 0);
+/// ```
+pub fn existing_before_len(content: &[String], template: &Template) -> usize {
+    let before = template.before.as_deref().unwrap_or_default();
+    // The empty string following the last line feed isn’t a line: it must stay
+    // after the header for the file to keep its trailing line feed.
+    let lines = match content.split_last() {
+        Some((last, lines)) if last.is_empty() => lines,
+        _ => content,
+    };
+    before
+        .iter()
+        .zip(lines)
+        .take_while(|(before, line)| before.trim_end() == line.trim_end())
+        .count()
+}
+
+/// Gets the content of a file once a new header is added to it.
+///
+/// # Arguments
+/// * `content` - Lines of the file.
+/// * `header` - New generated header.
+///
+/// # Example
+/// ```
+/// use auto_header::header::with_header;
+///
+/// let header = vec![String::from("// File: src/main.rs"), String::new()];
+/// let content = with_header(&[String::from("fn main() {}")], &header);
+/// assert_eq!(content, ["// File: src/main.rs", "", "fn main() {}"]);
+/// ```
+pub fn with_header(content: &[String], header: &[String]) -> Vec<String> {
+    header.iter().chain(content).cloned().collect()
+}
+
+/// Runs an action modifying a file, checking first that the file is writable.
+/// Read-only files are refused with a `ReadOnly` error (a skip for the command
+/// line), unless `chmod` is set: the file is then made writable for the
+/// duration of the action, and its permissions restored afterwards.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `chmod` - Whether read-only files should be made temporarily writable.
+/// * `action` - Action modifying the file.
+///
+/// # Example
+/// ```ignore
+/// let header = fill_template(&lang_conf, &project, &args.path, &project.root, &dates)?;
+/// let _ = with_write_access(&args.path, args.chmod, || write_header(&args.path, &header));
+/// ```
+pub fn with_write_access(
+    path: &str,
+    chmod: bool,
+    action: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let permissions = fs::metadata(path)
+        .map_err(AutoHeaderError::read(path))?
+        .permissions();
+    if !permissions.readonly() {
+        return action();
+    }
+    if !chmod {
+        return Err(AutoHeaderError::ReadOnly(path.to_owned()));
+    }
+    fs::set_permissions(path, writable(permissions.clone()))
+        .map_err(AutoHeaderError::write(path))?;
+    let res = action();
+    fs::set_permissions(path, permissions).map_err(AutoHeaderError::write(path))?;
+    res
+}
+
+/// Gives write access to the owner of a file.
+#[cfg(unix)]
+pub fn writable(permissions: fs::Permissions) -> fs::Permissions {
+    use std::os::unix::fs::PermissionsExt;
+    fs::Permissions::from_mode(permissions.mode() | 0o200)
+}
+
+/// Gives write access to a file.
+#[cfg(not(unix))]
+#[allow(clippy::permissions_set_readonly_false)]
+pub fn writable(mut permissions: fs::Permissions) -> fs::Permissions {
+    permissions.set_readonly(false);
+    permissions
+}
@@ -0,0 +1,919 @@
+//! Creation and update of file headers from configurable templates.
+//!
+//! The binary handles the command line and the file modifications, while this
+//! library exposes the building blocks (configuration, template filling, header
+//! detection) so that editor plugins and other integrations can reuse them.
+pub mod codeowners;
+pub mod config;
+pub mod diff;
+pub mod error;
+pub mod git;
+pub mod header;
+pub mod licenses;
+pub mod locales;
+pub mod logger;
+pub mod migrate;
+pub mod paths;
+pub mod report;
+
+use chrono::{DateTime, Local};
+use encoding_rs::UTF_8;
+
+use crate::{
+    config::{
+        default_locale, find_project, get_language, get_language_config, Config, LocalConfig,
+        Position, Project, Template,
+    },
+    diff::unified_diff,
+    error::{AutoHeaderError, Result},
+    git::Commit,
+    header::{
+        decode_lines, encode_lines, existing_before_len, fill_template, footer_start,
+        foreign_templates, has_birthtime, header_damaged, header_edited, header_exists, header_len,
+        header_line_count, normalize_prefixes, own_shebang_template, post_process, prelude_len,
+        read_head, rename_in_header, resign_header, set_modified, splice_file, template_encoding,
+        updated_content, with_header, with_write_access, written_modification_date, FileDates,
+    },
+    paths::absolute_path,
+};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Options controlling what can be done to a file.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Create the header if it’s absent.
+    pub create: bool,
+    /// Update the header if it exists.
+    pub update: bool,
+    /// Recreate damaged headers.
+    pub force: bool,
+    /// Temporarily make read-only files writable.
+    pub chmod: bool,
+    /// Directory in which the processed files are written (mirroring their
+    /// path relative to the project’s root), instead of modifying them in place.
+    pub output: Option<PathBuf>,
+    /// Date written in the headers as the current one (`#date_now`,
+    /// `#cp_year`), for reproducible headers. The modification times of the
+    /// files are still set to the current date.
+    pub timestamp: Option<DateTime<Local>>,
+    /// Only determine what would be done to the files, without writing anything.
+    pub dry_run: bool,
+    /// Only rewrite the bytes of the files that changed, instead of the whole
+    /// files.
+    pub only_changed_lines: bool,
+    /// Fail on files whose creation date is unavailable, instead of using
+    /// their modification date.
+    pub require_birthtime: bool,
+    /// Don’t create headers in files that are empty or hold only whitespace.
+    pub skip_empty: bool,
+    /// Rewrite the comment prefix of every line of the existing headers to be
+    /// exactly the template’s, not only the tracked lines.
+    pub normalize_prefix: bool,
+    /// Take the dates of the files from their git history (`use_git`).
+    pub use_git: bool,
+    /// Commits changing the file, when already fetched (the history is
+    /// otherwise read again when `use_git` is set).
+    pub commits: Option<Vec<Commit>>,
+}
+
+/// What was done to a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// A header was added to the file.
+    Created,
+    /// The tracked lines of the existing header were updated.
+    Updated,
+    /// A damaged header was replaced by a complete one.
+    Recreated,
+    /// A header written with another comment syntax was replaced.
+    Replaced,
+    /// The existing header is already up to date.
+    UpToDate,
+    /// The options forbid doing anything to the file.
+    Skipped {
+        /// Whether the file has a header.
+        header_present: bool,
+    },
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Created => "created",
+            Action::Updated => "updated",
+            Action::Recreated => "recreated",
+            Action::Replaced => "replaced",
+            Action::UpToDate => "up-to-date",
+            Action::Skipped { .. } => "skipped",
+        })
+    }
+}
+
+/// Gets the header lines that would be generated for a file, without modifying
+/// it (only its metadata are read).
+///
+/// Fails if no project or no template applies to the file.
+///
+/// # Arguments
+/// * `config` - Global configuration.
+/// * `path` - Path to the file.
+///
+/// # Example
+/// ```ignore
+/// let config = Config::parse(&fs::read_to_string(config_path)?)?;
+/// let header = header_lines(&config, "./src/main.rs")?;
+/// println!("{}", header.join("\n"));
+/// ```
+pub fn header_lines(config: &Config, path: &str) -> Result<Vec<String>> {
+    let (project, template) = resolve(config, path)?;
+    let dates = FileDates::from_file(path)?;
+    post_process(
+        fill_template(&template, &project, path, &project.root, &dates)?,
+        &template,
+    )
+}
+
+/// Resolves the project and the template that apply to a file.
+///
+/// Fails if the default template is incomplete, or if no project or no
+/// template applies to the file.
+///
+/// # Arguments
+/// * `config` - Global configuration.
+/// * `path` - Path to the file.
+///
+/// # Example
+/// ```
+/// use auto_header::resolve;
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// let (project, template) = resolve(&config, "/home/user/project/src/main.rs")?;
+/// assert_eq!(project.root, "/home/user/project");
+/// assert_eq!(template.prefix.as_deref(), Some("// "));
+/// assert!(resolve(&config, "/home/user/elsewhere/main.rs").is_err());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn resolve(config: &Config, path: &str) -> Result<(Project, Template)> {
+    config.check_default()?;
+    let project = find_project(config, path)?
+        .ok_or_else(|| AutoHeaderError::NoProject(path.to_owned()))?
+        .resolve(config);
+    let locals = LocalConfig::find(&project, path)?;
+    let project = project.with_locals(&locals);
+    let language = get_language(config, path);
+    let template = get_language_config(config, &language)
+        .ok_or_else(|| AutoHeaderError::NoLanguage {
+            path: path.to_owned(),
+            language,
+        })?
+        .merge(&config.default)
+        .with_locals(&locals)
+        .with_project(&project);
+    let template = config.with_includes(template)?;
+    if let Some(data) = &project.data {
+        data.check(&template)?;
+    }
+    Ok((project, template))
+}
+
+/// Creates or updates the header of some content, without touching the disk.
+///
+/// # Arguments
+/// * `path` - Path of the file the content belongs to (used to fill the template).
+/// * `content` - Lines of the content.
+/// * `dates` - Dates of the content.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - What can be done to the content.
+///
+/// # Example
+/// ```
+/// use auto_header::{header::FileDates, process_content, resolve, Action, Options};
+///
+/// # let config = auto_header::config::Config::parse(r##"
+/// # [data]
+/// # author = "Jane Doe"
+/// # [default]
+/// # name = "*"
+/// # prefix = "# "
+/// # before = []
+/// # after = [""]
+/// # template = "File: #file_relative_path\nAuthor: #author"
+/// # track_changes = ["File: "]
+/// # [[language]]
+/// # name = "rust"
+/// # prefix = "// "
+/// # [[project]]
+/// # root = "/home/user/project"
+/// # "##)?;
+/// let path = "/home/user/project/src/main.rs";
+/// let (project, template) = resolve(&config, path)?;
+/// let options = Options { create: true, update: true, ..Default::default() };
+/// let content = vec![String::from("fn main() {}"), String::new()];
+/// let dates = FileDates::now();
+/// let (action, content) = process_content(path, &content, &dates, &project, &template, &options)?;
+/// assert_eq!(action, Action::Created);
+/// assert_eq!(content, ["// File: src/main.rs", "// Author: Jane Doe", "", "fn main() {}", ""]);
+/// let (action, _) = process_content(path, &content, &dates, &project, &template, &options)?;
+/// assert_eq!(action, Action::UpToDate);
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn process_content(
+    path: &str,
+    content: &[String],
+    dates: &FileDates,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+) -> Result<(Action, Vec<String>)> {
+    if template.position.unwrap_or_default() == Position::Bottom {
+        return process_footer(path, content, dates, project, template, options);
+    }
+    let adjusted = own_shebang_template(content, template);
+    let template = adjusted.as_ref().unwrap_or(template);
+    // The header is handled after the leading lines that must stay first.
+    let mut prelude = prelude_len(content, template)?;
+    // A header created in a file shorter than its leading lines ends them: it
+    // then starts right after the file’s own lines.
+    if prelude > 0 && template.end_marker.is_none() {
+        let header = post_process(
+            fill_template(template, project, path, &project.root, dates)?,
+            template,
+        )?;
+        if let Some(start) =
+            (0..prelude).find(|&start| header_exists(&content[start..], &header, template))
+        {
+            prelude = start;
+        }
+    }
+    // The blank lines before the header are only handled if their number is set
+    // (the empty string following the final line feed is never one of them).
+    let blank = match template.blank_lines_before {
+        Some(_) => content[prelude..content.len().saturating_sub(1).max(prelude)]
+            .iter()
+            .take_while(|line| line.trim().is_empty())
+            .count(),
+        None => 0,
+    };
+    let start = prelude + blank;
+    // Leading lines filling the whole content (without a final line feed) are
+    // followed by an empty body, handled as an empty file.
+    let body = match &content[start..] {
+        [] => &[String::new()][..],
+        body => body,
+    };
+    let (action, body) = process_body(path, body, dates, project, template, options)?;
+    let spacing = match (template.blank_lines_before, action) {
+        (Some(_), Action::UpToDate | Action::Skipped { .. }) | (None, _) => {
+            content[prelude..start].to_vec()
+        }
+        (Some(count), _) => vec![String::new(); count],
+    };
+    Ok((action, [&content[..prelude], &spacing, &body].concat()))
+}
+
+/// Creates or updates the header at the end of some content
+/// (`position = "bottom"`).
+///
+/// # Arguments
+/// * `path` - Path of the file the content belongs to (used to fill the template).
+/// * `content` - Lines of the content.
+/// * `dates` - Dates of the content.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - What can be done to the content.
+fn process_footer(
+    path: &str,
+    content: &[String],
+    dates: &FileDates,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+) -> Result<(Action, Vec<String>)> {
+    let header = post_process(
+        fill_template(template, project, path, &project.root, dates)?,
+        template,
+    )?;
+    header_line_count(&header, template)?;
+    // Lines of the content, but the empty one following the final line feed.
+    let end = content.len() - usize::from(content.last().is_some_and(String::is_empty));
+    if let Some(start) = footer_start(content, &header, template) {
+        let (action, footer) = process_body(
+            path,
+            &content[start..end],
+            dates,
+            project,
+            template,
+            options,
+        )?;
+        return Ok((
+            action,
+            [&content[..start], &footer, &content[end..]].concat(),
+        ));
+    }
+    if !options.create || (options.skip_empty && is_blank(content)) {
+        return Ok((
+            Action::Skipped {
+                header_present: false,
+            },
+            content.to_vec(),
+        ));
+    }
+    let header = post_process(
+        fill_template(template, project, path, &project.root, &dates.touched())?,
+        template,
+    )?;
+    Ok((
+        Action::Created,
+        [&content[..end], &header, &[String::new()]].concat(),
+    ))
+}
+
+/// Creates or updates the header at the beginning of some content.
+///
+/// # Arguments
+/// * `path` - Path of the file the content belongs to (used to fill the template).
+/// * `content` - Lines of the content, from where the header is expected.
+/// * `dates` - Dates of the content.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - What can be done to the content.
+fn process_body(
+    path: &str,
+    content: &[String],
+    dates: &FileDates,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+) -> Result<(Action, Vec<String>)> {
+    let header = post_process(
+        fill_template(template, project, path, &project.root, dates)?,
+        template,
+    )?;
+    header_line_count(&header, template)?;
+    let header_present = header_exists(content, &header, template);
+    // Only generated if the content is modified.
+    let new_header = || {
+        post_process(
+            fill_template(template, project, path, &project.root, &dates.touched())?,
+            template,
+        )
+    };
+
+    Ok(
+        if !header_present && header_damaged(content, &header, template) {
+            if !options.force {
+                return Err(AutoHeaderError::DamagedHeader(path.to_owned()));
+            }
+            (
+                Action::Recreated,
+                with_header(&[String::new()], &new_header()?),
+            )
+        } else if header_present && options.update {
+            let updated = |header: &[String]| {
+                updated_content(path, content, header, template)
+                    .map(|updated| {
+                        if options.normalize_prefix {
+                            normalize_prefixes(&updated, header, template)
+                        } else {
+                            updated
+                        }
+                    })
+                    .map(|updated| resign_header(&updated, header, template))
+            };
+            if updated(&header)? == content {
+                (Action::UpToDate, content.to_vec())
+            } else if header_edited(content, &header, template) && !options.force {
+                return Err(AutoHeaderError::EditedHeader(path.to_owned()));
+            } else {
+                (Action::Updated, updated(&new_header()?)?)
+            }
+        } else if let Some(len) = (!header_present)
+            .then(|| foreign_header_len(path, content, dates, project, template))
+            .transpose()?
+            .flatten()
+        {
+            if !options.force {
+                return Err(AutoHeaderError::ForeignHeader(path.to_owned()));
+            }
+            (
+                Action::Replaced,
+                with_header(&content[len..], &new_header()?),
+            )
+        } else if !header_present && options.create && !(options.skip_empty && is_blank(content)) {
+            let existing = existing_before_len(content, template);
+            (
+                Action::Created,
+                with_header(&content[existing..], &new_header()?),
+            )
+        } else {
+            (Action::Skipped { header_present }, content.to_vec())
+        },
+    )
+}
+
+/// Checks whether some content is empty or only holds whitespace.
+///
+/// # Arguments
+/// * `content` - Lines of the content.
+fn is_blank(content: &[String]) -> bool {
+    content.iter().all(|line| line.trim().is_empty())
+}
+
+/// Gets the number of lines of a header written with another comment syntax
+/// than the template’s, if the content starts with one.
+///
+/// # Arguments
+/// * `path` - Path of the file the content belongs to.
+/// * `content` - Lines of the content.
+/// * `dates` - Dates of the content.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+fn foreign_header_len(
+    path: &str,
+    content: &[String],
+    dates: &FileDates,
+    project: &Project,
+    template: &Template,
+) -> Result<Option<usize>> {
+    for foreign in foreign_templates(template) {
+        let header = post_process(
+            fill_template(&foreign, project, path, &project.root, dates)?,
+            &foreign,
+        )?;
+        if header_exists(content, &header, &foreign) {
+            return Ok(Some(header_len(content, &header, &foreign)));
+        }
+    }
+    Ok(None)
+}
+
+/// Creates or updates the header of a file.
+///
+/// Whenever the file is modified, the header is generated with the current date
+/// as modification date, and the file’s modification time is set to that same
+/// date: running the process again on the file is then a no-op. A timestamp
+/// given in the options is only written in the header.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - What can be done to the file.
+///
+/// # Example
+/// ```ignore
+/// let (project, template) = resolve(&config, "./src/main.rs")?;
+/// let options = Options { create: true, update: true, ..Default::default() };
+/// let action = process_file("./src/main.rs", &project, &template, &options)?;
+/// ```
+pub fn process_file(
+    path: &str,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+) -> Result<Action> {
+    if options.output.is_none() && head_up_to_date(path, project, template, options)? {
+        return Ok(Action::UpToDate);
+    }
+    let (content, dates) = read_content(path, template, options)?;
+    let (action, new_content) =
+        process_content(path, &content, &dates, project, template, options)?;
+
+    // A fixed timestamp is only written in the header: the file keeps its own
+    // modification time, or gets the current date if it’s modified.
+    let unchanged = matches!(action, Action::UpToDate | Action::Skipped { .. });
+    let modified = match (unchanged, options.timestamp) {
+        (true, None) => dates.modified,
+        (true, Some(_)) => FileDates::from_file(path)?.modified,
+        (false, None) => dates.now,
+        (false, Some(_)) => Local::now(),
+    };
+    let changed = !unchanged;
+    write_content(
+        path,
+        project,
+        template,
+        options,
+        &new_content,
+        changed,
+        &modified,
+    )?;
+    Ok(action)
+}
+
+/// Checks that the header of a file is up to date, without modifying it. A
+/// header that would be created, recreated, replaced or updated fails the
+/// check, unless only its modification date lags behind the file’s by no more
+/// than `max_age_days` days: that header is then considered up to date.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - What could be done to the file.
+/// * `max_age_days` - Number of days the header’s modification date can lag
+///   behind the file’s.
+///
+/// # Example
+/// ```ignore
+/// let (project, template) = resolve(&config, "./src/main.rs")?;
+/// let action = check_file("./src/main.rs", &project, &template, &options, config.max_age_days)?;
+/// ```
+pub fn check_file(
+    path: &str,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+    max_age_days: Option<u32>,
+) -> Result<Action> {
+    if head_up_to_date(path, project, template, options)? {
+        return Ok(Action::UpToDate);
+    }
+    let (content, dates) = read_content(path, template, options)?;
+    let (action, _) = process_content(path, &content, &dates, project, template, options)?;
+    let outdated = || AutoHeaderError::OutdatedHeader {
+        path: path.to_owned(),
+        action,
+    };
+    match action {
+        Action::UpToDate | Action::Skipped { .. } => return Ok(action),
+        Action::Updated => {}
+        _ => return Err(outdated()),
+    }
+    let Some(max_age_days) = max_age_days else {
+        return Err(outdated());
+    };
+
+    let adjusted = own_shebang_template(&content, template);
+    let template = adjusted.as_ref().unwrap_or(template);
+    let header = post_process(
+        fill_template(template, project, path, &project.root, &dates)?,
+        template,
+    )?;
+    let locale = project.locale.clone().unwrap_or(default_locale());
+    let prelude = prelude_len(&content, template)?;
+    let Some(written) = written_modification_date(&content[prelude..], &header, template, &locale)
+    else {
+        return Err(outdated());
+    };
+    // The header must be up to date but for its modification date.
+    let written_dates = FileDates {
+        modified: written,
+        ..dates.clone()
+    };
+    let (action, _) = process_content(path, &content, &written_dates, project, template, options)?;
+    if action != Action::UpToDate {
+        return Err(outdated());
+    }
+    let days = (dates.modified - written).num_days();
+    if days > i64::from(max_age_days) {
+        return Err(AutoHeaderError::StaleHeader {
+            path: path.to_owned(),
+            days,
+        });
+    }
+    Ok(Action::UpToDate)
+}
+
+/// Gets the changes that would be made to the header of a file, as a unified
+/// diff that `git apply` accepts (paths relative to the project’s root). The
+/// file isn’t modified, and the diff is empty if nothing would change.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - What could be done to the file.
+///
+/// # Example
+/// ```ignore
+/// let (project, template) = resolve(&config, "./src/main.rs")?;
+/// let (action, diff) = file_diff("./src/main.rs", &project, &template, &options)?;
+/// print!("{}", diff);
+/// ```
+pub fn file_diff(
+    path: &str,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+) -> Result<(Action, String)> {
+    let outcome = file_outcome(path, project, template, options)?;
+    Ok((outcome.action, outcome.diff()))
+}
+
+/// What processing a file would do: the action, and the content of the file
+/// before and after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// Path of the file, relative to its project’s root.
+    pub path: String,
+    /// What would be done to the file.
+    pub action: Action,
+    /// Lines of the file (as read by `read_lines`).
+    pub before: Vec<String>,
+    /// Lines of the processed file.
+    pub after: Vec<String>,
+}
+
+impl Outcome {
+    /// Renders the changes as a unified diff that `git apply` accepts (empty
+    /// if nothing would change).
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::{Action, Outcome};
+    ///
+    /// let outcome = Outcome {
+    ///     path: String::from("src/main.rs"),
+    ///     action: Action::Created,
+    ///     before: vec![String::from("fn main() {}"), String::new()],
+    ///     after: vec![String::from("// File: src/main.rs"), String::from("fn main() {}"), String::new()],
+    /// };
+    /// assert!(outcome.diff().contains("\n+// File: src/main.rs\n"));
+    /// ```
+    pub fn diff(&self) -> String {
+        unified_diff(&self.path, &self.before, &self.after)
+    }
+}
+
+/// Gets what processing a file would do, without modifying it.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - What could be done to the file.
+///
+/// # Example
+/// ```ignore
+/// let (project, template) = resolve(&config, "./src/main.rs")?;
+/// let outcome = file_outcome("./src/main.rs", &project, &template, &options)?;
+/// if outcome.action != Action::UpToDate {
+///     print!("{}", outcome.diff());
+/// }
+/// ```
+pub fn file_outcome(
+    path: &str,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+) -> Result<Outcome> {
+    let (before, dates) = read_content(path, template, options)?;
+    let (action, after) = process_content(path, &before, &dates, project, template, options)?;
+    Ok(Outcome {
+        path: relative_path(path, project)?.to_string_lossy().into_owned(),
+        action,
+        before,
+        after,
+    })
+}
+
+/// Reads the lines of a file and its dates, the current one being the
+/// options’ timestamp if there’s one.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `template` - Template of the header (merged), giving the file’s encoding.
+/// * `options` - Options of the processing.
+fn read_content(
+    path: &str,
+    template: &Template,
+    options: &Options,
+) -> Result<(Vec<String>, FileDates)> {
+    let dates = read_dates(path, options)?;
+    let encoding = template_encoding(template)?;
+    let bytes = fs::read(path).map_err(AutoHeaderError::read(path))?;
+    let content = decode_lines(path, &bytes, encoding)?;
+    Ok((content, dates))
+}
+
+/// Reads the dates of a file, the current and modification ones being the
+/// options’ timestamp if there’s one (written in the header as is).
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `options` - Options of the processing.
+fn read_dates(path: &str, options: &Options) -> Result<FileDates> {
+    if options.require_birthtime && !has_birthtime(path) {
+        return Err(AutoHeaderError::MissingBirthtime(path.to_owned()));
+    }
+    let mut dates = file_dates(path, options)?;
+    if let Some(timestamp) = options.timestamp {
+        dates.modified = timestamp;
+        dates.now = timestamp;
+    }
+    Ok(dates)
+}
+
+/// Checks whether the header of a file is up to date by reading only the
+/// lines that can hold it, when nothing is to be written and the template
+/// gives the header’s line count (`header_lines`). The file must be read
+/// entirely otherwise, or when the header isn’t up to date.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - Options of the processing.
+fn head_up_to_date(
+    path: &str,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+) -> Result<bool> {
+    let Some(hint) = template.header_lines else {
+        return Ok(false);
+    };
+    // The lines before the header must be bounded too (a shebang at most).
+    let bounded = template.position.unwrap_or_default() == Position::Top
+        && template.insert_after.is_none()
+        && template.blank_lines_before.is_none()
+        && template_encoding(template)? == UTF_8;
+    if !options.dry_run || !bounded {
+        return Ok(false);
+    }
+    let leading = template.protect_leading_lines.unwrap_or(0).max(2);
+    let (head, rest) = read_head(path, hint + leading)?;
+    // A file no longer than its head is read entirely anyway.
+    if rest.is_none() {
+        return Ok(false);
+    }
+    let dates = read_dates(path, options)?;
+    let (action, _) = process_content(path, &head, &dates, project, template, options)?;
+    Ok(action == Action::UpToDate)
+}
+
+/// Gets the dates of a file, from its git history if the options ask for it.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `options` - Options of the processing.
+fn file_dates(path: &str, options: &Options) -> Result<FileDates> {
+    if options.use_git {
+        match &options.commits {
+            Some(commits) => FileDates::from_commits(path, commits),
+            None => FileDates::from_history(path),
+        }
+    } else {
+        FileDates::from_file(path)
+    }
+}
+
+/// Gets the path of a file relative to its project’s root, or its name if
+/// it’s outside of it.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+///
+/// # Example
+/// ```
+/// use auto_header::{config::Project, relative_path};
+/// use std::path::Path;
+///
+/// let project = Project { root: String::from("/home/user/project"), ..Default::default() };
+/// assert_eq!(relative_path("/home/user/project/src/main.rs", &project)?, Path::new("src/main.rs"));
+/// assert_eq!(relative_path("/home/user/elsewhere/main.rs", &project)?, Path::new("main.rs"));
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn relative_path(path: &str, project: &Project) -> Result<PathBuf> {
+    let absolute = absolute_path(Path::new(path))?;
+    Ok(absolute
+        .strip_prefix(&project.root)
+        .ok()
+        .or(absolute.file_name().map(Path::new))
+        .unwrap_or(Path::new(path))
+        .to_path_buf())
+}
+
+/// Replaces an author’s name or mail address in the existing header of a file
+/// (after a rebranding or a domain migration for example). The rest of the
+/// file, and its modification date, are left untouched.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - Options of the processing.
+/// * `from` - Name or mail address to replace.
+/// * `to` - New name or mail address.
+///
+/// # Example
+/// ```ignore
+/// let (project, template) = resolve(&config, "./src/main.rs")?;
+/// let action = rename_author("./src/main.rs", &project, &template, &options, "old@x.org", "new@y.org")?;
+/// ```
+pub fn rename_author(
+    path: &str,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+    from: &str,
+    to: &str,
+) -> Result<Action> {
+    let encoding = template_encoding(template)?;
+    let bytes = fs::read(path).map_err(AutoHeaderError::read(path))?;
+    let content = decode_lines(path, &bytes, encoding)?;
+    let dates = file_dates(path, options)?;
+    let adjusted = own_shebang_template(&content, template);
+    let template = adjusted.as_ref().unwrap_or(template);
+    let header = post_process(
+        fill_template(template, project, path, &project.root, &dates)?,
+        template,
+    )?;
+    let prelude = prelude_len(&content, template)?;
+    let body = &content[prelude..];
+    let renamed = resign_header(
+        &rename_in_header(body, &header, template, from, to),
+        &header,
+        template,
+    );
+    // The configuration may already use the new name.
+    if !header_exists(body, &header, template) && !header_exists(&renamed, &header, template) {
+        return Ok(Action::Skipped {
+            header_present: false,
+        });
+    }
+    let changed = renamed != body;
+    let renamed = [&content[..prelude], &renamed].concat();
+    write_content(
+        path,
+        project,
+        template,
+        options,
+        &renamed,
+        changed,
+        &dates.modified,
+    )?;
+    Ok(if changed {
+        Action::Updated
+    } else {
+        Action::UpToDate
+    })
+}
+
+/// Writes the processed content of a file, in place or in the output
+/// directory, and sets its modification date.
+///
+/// # Arguments
+/// * `path` - Path to the file.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the header (merged).
+/// * `options` - Options of the processing.
+/// * `content` - Processed lines of the file.
+/// * `changed` - Whether the content differs from the file’s.
+/// * `modified` - Modification date of the file once written.
+fn write_content(
+    path: &str,
+    project: &Project,
+    template: &Template,
+    options: &Options,
+    content: &[String],
+    changed: bool,
+    modified: &DateTime<Local>,
+) -> Result<()> {
+    if options.dry_run {
+        return Ok(());
+    }
+    let encoding = template_encoding(template)?;
+    if let Some(output) = &options.output {
+        let destination = output.join(relative_path(path, project)?);
+        let destination = destination.to_string_lossy();
+        if let Some(parent) = Path::new(destination.as_ref()).parent() {
+            fs::create_dir_all(parent).map_err(AutoHeaderError::write(&destination))?;
+        }
+        fs::write(destination.as_ref(), encode_lines(path, content, encoding)?)
+            .map_err(AutoHeaderError::write(&destination))?;
+        set_modified(&destination, modified)?;
+    } else if changed {
+        with_write_access(path, options.chmod, || {
+            let content = encode_lines(path, content, encoding)?;
+            if options.only_changed_lines {
+                splice_file(path, &content)?;
+            } else {
+                fs::write(path, content).map_err(AutoHeaderError::write(path))?;
+            }
+            set_modified(path, modified)
+        })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,358 @@
+use chrono::{
+    format::{parse, Parsed, StrftimeItems},
+    DateTime, Datelike, NaiveDateTime, TimeZone, Weekday,
+};
+use std::fmt::Display;
+
+/// Names of the days and months for a given locale.
+struct LocaleNames {
+    /// Locale identifier (language part only).
+    id: &'static str,
+    /// Full names of the days, starting on monday.
+    days: [&'static str; 7],
+    /// Abbreviated names of the days, starting on monday.
+    days_abbrev: [&'static str; 7],
+    /// Full names of the months.
+    months: [&'static str; 12],
+    /// Abbreviated names of the months.
+    months_abbrev: [&'static str; 12],
+}
+
+/// Locales bundled with the application, so that date formatting never
+/// depends on the locale data installed on the host.
+const LOCALES: [LocaleNames; 6] = [
+    LocaleNames {
+        id: "en",
+        days: [
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+            "Sunday",
+        ],
+        days_abbrev: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+        months: [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        months_abbrev: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+    },
+    LocaleNames {
+        id: "fr",
+        days: [
+            "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+        ],
+        days_abbrev: ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+        months: [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+        months_abbrev: [
+            "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+            "nov.", "déc.",
+        ],
+    },
+    LocaleNames {
+        id: "de",
+        days: [
+            "Montag",
+            "Dienstag",
+            "Mittwoch",
+            "Donnerstag",
+            "Freitag",
+            "Samstag",
+            "Sonntag",
+        ],
+        days_abbrev: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+        months: [
+            "Januar",
+            "Februar",
+            "März",
+            "April",
+            "Mai",
+            "Juni",
+            "Juli",
+            "August",
+            "September",
+            "Oktober",
+            "November",
+            "Dezember",
+        ],
+        months_abbrev: [
+            "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+        ],
+    },
+    LocaleNames {
+        id: "es",
+        days: [
+            "lunes",
+            "martes",
+            "miércoles",
+            "jueves",
+            "viernes",
+            "sábado",
+            "domingo",
+        ],
+        days_abbrev: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+        months: [
+            "enero",
+            "febrero",
+            "marzo",
+            "abril",
+            "mayo",
+            "junio",
+            "julio",
+            "agosto",
+            "septiembre",
+            "octubre",
+            "noviembre",
+            "diciembre",
+        ],
+        months_abbrev: [
+            "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+        ],
+    },
+    LocaleNames {
+        id: "it",
+        days: [
+            "lunedì",
+            "martedì",
+            "mercoledì",
+            "giovedì",
+            "venerdì",
+            "sabato",
+            "domenica",
+        ],
+        days_abbrev: ["lun", "mar", "mer", "gio", "ven", "sab", "dom"],
+        months: [
+            "gennaio",
+            "febbraio",
+            "marzo",
+            "aprile",
+            "maggio",
+            "giugno",
+            "luglio",
+            "agosto",
+            "settembre",
+            "ottobre",
+            "novembre",
+            "dicembre",
+        ],
+        months_abbrev: [
+            "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+        ],
+    },
+    LocaleNames {
+        id: "pt",
+        days: [
+            "segunda-feira",
+            "terça-feira",
+            "quarta-feira",
+            "quinta-feira",
+            "sexta-feira",
+            "sábado",
+            "domingo",
+        ],
+        days_abbrev: ["seg", "ter", "qua", "qui", "sex", "sáb", "dom"],
+        months: [
+            "janeiro",
+            "fevereiro",
+            "março",
+            "abril",
+            "maio",
+            "junho",
+            "julho",
+            "agosto",
+            "setembro",
+            "outubro",
+            "novembro",
+            "dezembro",
+        ],
+        months_abbrev: [
+            "jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+        ],
+    },
+];
+
+/// Gets the bundled names for a locale, ignoring any region or encoding
+/// suffix (`fr_FR.UTF-8` is treated as `fr`).
+fn get_locale(locale: &str) -> Option<&'static LocaleNames> {
+    let language = locale
+        .split(['_', '-', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    LOCALES.iter().find(|l| l.id == language)
+}
+
+/// Checks if a locale is supported by the bundled tables.
+///
+/// # Arguments
+/// * `locale` - Locale identifier (`fr`, `en_US`, …).
+///
+/// # Example
+/// ```
+/// use auto_header::locales::is_supported_locale;
+///
+/// assert!(is_supported_locale("fr"));
+/// assert!(is_supported_locale("en_US"));
+/// assert!(!is_supported_locale("tlh"));
+/// ```
+pub fn is_supported_locale(locale: &str) -> bool {
+    get_locale(locale).is_some()
+}
+
+/// Formats a date, using the bundled locale tables for the names of the days
+/// and months. Unsupported locales are rendered in English.
+///
+/// # Arguments
+/// * `date` - Date to format.
+/// * `format` - `strftime` like format.
+/// * `locale` - Locale identifier.
+/// * `capitalize` - Whether the names of the days and months are capitalized.
+///
+/// # Example
+/// ```
+/// use auto_header::locales::format_date;
+/// use chrono::{TimeZone, Utc};
+///
+/// let date = Utc.with_ymd_and_hms(2026, 10, 15, 8, 21, 19).unwrap();
+/// assert_eq!(format_date(&date, "%A %d %B %Y", "fr", false), "jeudi 15 octobre 2026");
+/// assert_eq!(format_date(&date, "%A %d %B %Y", "fr", true), "Jeudi 15 Octobre 2026");
+/// assert_eq!(format_date(&date, "%A %d %B %Y", "tlh", false), "Thursday 15 October 2026");
+/// ```
+pub fn format_date<Tz: TimeZone>(
+    date: &DateTime<Tz>,
+    format: &str,
+    locale: &str,
+    capitalize: bool,
+) -> String
+where
+    Tz::Offset: Display,
+{
+    let names = get_locale(locale).unwrap_or(&LOCALES[0]);
+    let day = date.weekday().num_days_from_monday() as usize;
+    let month = date.month0() as usize;
+    date.format(&localize_format(format, names, day, month, capitalize))
+        .to_string()
+}
+
+/// Parses a date formatted by `format_date` (the offset being unknown, the
+/// date is naive). Nothing is returned if the text doesn’t match the format.
+///
+/// # Arguments
+/// * `text` - Formatted date.
+/// * `format` - `strftime` like format the date was written with.
+/// * `locale` - Locale identifier.
+///
+/// # Example
+/// ```
+/// use auto_header::locales::parse_date;
+/// use chrono::NaiveDate;
+///
+/// let date = parse_date("jeudi 15 octobre 2026 @ 08:21:19", "%A %d %B %Y @ %H:%M:%S", "fr");
+/// assert_eq!(date, NaiveDate::from_ymd_opt(2026, 10, 15).unwrap().and_hms_opt(8, 21, 19));
+/// assert_eq!(parse_date("15/10/2026", "%A %d %B %Y", "fr"), None);
+/// ```
+pub fn parse_date(text: &str, format: &str, locale: &str) -> Option<NaiveDateTime> {
+    let names = get_locale(locale).unwrap_or(&LOCALES[0]);
+    let names_day = format.contains("%A") || format.contains("%a");
+    let names_month = ["%B", "%b", "%h"].iter().any(|spec| format.contains(spec));
+    // The names are written literally in the format, so every day and month is tried.
+    for capitalize in [false, true] {
+        for month in 0..12 {
+            for day in 0..7 {
+                let localized = localize_format(format, names, day, month, capitalize);
+                let mut parsed = Parsed::default();
+                if parse(&mut parsed, text, StrftimeItems::new(&localized)).is_err()
+                    || (names_month && parsed.set_month(month as i64 + 1).is_err())
+                    || (names_day
+                        && parsed
+                            .set_weekday(Weekday::try_from(day as u8).ok()?)
+                            .is_err())
+                {
+                    continue;
+                }
+                // The weekday is checked against the date.
+                if let Ok(date) = parsed.to_naive_datetime_with_offset(0) {
+                    return Some(date);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Replaces the names of the days and months in a format by their localized
+/// version, leaving the other specifiers to `chrono`.
+///
+/// # Arguments
+/// * `format` - `strftime` like format.
+/// * `names` - Names of the locale.
+/// * `day` - Day of the week (from monday, starting at 0).
+/// * `month` - Month (starting at 0).
+/// * `capitalize` - Whether the names are capitalized.
+fn localize_format(
+    format: &str,
+    names: &LocaleNames,
+    day: usize,
+    month: usize,
+    capitalize: bool,
+) -> String {
+    let mut localized = String::with_capacity(format.len());
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            localized.push(c);
+            continue;
+        }
+        let name = match chars.next() {
+            Some('A') => names.days[day],
+            Some('a') => names.days_abbrev[day],
+            Some('B') => names.months[month],
+            Some('b') | Some('h') => names.months_abbrev[month],
+            Some(other) => {
+                localized.push('%');
+                localized.push(other);
+                continue;
+            }
+            None => {
+                localized.push('%');
+                continue;
+            }
+        };
+        let mut name_chars = name.chars();
+        match name_chars.next() {
+            Some(first) if capitalize => {
+                localized.extend(first.to_uppercase());
+                localized.push_str(name_chars.as_str());
+            }
+            _ => localized.push_str(name),
+        }
+    }
+    localized
+}
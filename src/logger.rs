@@ -0,0 +1,150 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::Mutex,
+};
+
+/// Lock held while writing to the terminal, so that messages coming from
+/// different threads are never interleaved.
+static OUTPUT: Mutex<()> = Mutex::new(());
+
+/// Severity of a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    /// Informative message.
+    Info,
+    /// Something went wrong while processing the file.
+    Error,
+}
+
+/// Messages emitted while processing a single file.
+///
+/// Messages are buffered and written all at once when the log is flushed
+/// (or dropped), so that the output of a given file is always grouped even
+/// if several files are processed concurrently.
+#[derive(Debug, Default)]
+pub struct FileLog {
+    /// Buffered messages, in emission order.
+    messages: Vec<(Level, String)>,
+    /// Write the messages on the standard error instead of the standard
+    /// output (which then only holds the processed content).
+    stderr: bool,
+    /// Drop the informative messages, only errors are written.
+    quiet: bool,
+    /// Write the errors in red, when they’re written on a terminal.
+    color: bool,
+}
+
+impl FileLog {
+    /// Creates an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty log, written on the standard error.
+    pub fn stderr() -> Self {
+        Self {
+            messages: Vec::new(),
+            stderr: true,
+            ..Default::default()
+        }
+    }
+
+    /// Makes the log drop its informative messages, or not.
+    ///
+    /// # Arguments
+    /// * `quiet` - Whether only errors are written.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::logger::FileLog;
+    ///
+    /// let mut log = FileLog::new().quiet(true);
+    /// log.info("Dropped: the log is quiet.");
+    /// log.flush();
+    /// ```
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Makes the log write its errors in red when they’re written on a
+    /// terminal, or not.
+    ///
+    /// # Arguments
+    /// * `color` - Whether colors are allowed.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::logger::FileLog;
+    ///
+    /// let mut log = FileLog::new().color(false);
+    /// log.error("Written without colors.");
+    /// log.flush();
+    /// ```
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Adds an informative message to the log.
+    ///
+    /// # Arguments
+    /// * `message` - Message to add.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::logger::FileLog;
+    ///
+    /// let mut log = FileLog::new();
+    /// log.info(format!("No configuration found for file {}. Exiting.", "src/main.rs"));
+    /// log.flush();
+    /// ```
+    pub fn info(&mut self, message: impl Into<String>) {
+        if !self.quiet {
+            self.messages.push((Level::Info, message.into()));
+        }
+    }
+
+    /// Adds an error message to the log.
+    ///
+    /// # Arguments
+    /// * `message` - Message to add.
+    ///
+    /// # Example
+    /// ```
+    /// use auto_header::logger::FileLog;
+    ///
+    /// let mut log = FileLog::new();
+    /// log.error(format!("Failed to update header: {}", "src/main.rs is read-only"));
+    /// log.flush();
+    /// ```
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.messages.push((Level::Error, message.into()));
+    }
+
+    /// Writes all buffered messages at once: errors on the standard error,
+    /// informative messages on the standard output.
+    pub fn flush(&mut self) {
+        if self.messages.is_empty() {
+            return;
+        }
+        let _guard = OUTPUT.lock().unwrap_or_else(|err| err.into_inner());
+        let mut stdout = io::stdout().lock();
+        let mut stderr = io::stderr().lock();
+        let red = self.color && io::stderr().is_terminal();
+        for (level, message) in self.messages.drain(..) {
+            let _ = match level {
+                Level::Error if red => writeln!(stderr, "\x1b[31m{}\x1b[0m", message),
+                Level::Error => writeln!(stderr, "{}", message),
+                Level::Info if self.stderr => writeln!(stderr, "{}", message),
+                Level::Info => writeln!(stdout, "{}", message),
+            };
+        }
+    }
+}
+
+impl Drop for FileLog {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
@@ -9,544 +9,1348 @@
 // Copyright © 2023 <Vincent Berthier> - All rights reserved
 #![allow(dead_code)]
 
-mod licenses;
-
+use auto_header::{
+    check_file,
+    config::{
+        config_path, find_project, get_language, get_language_config, get_named_template, Config,
+        LocalConfig, Project, Template,
+    },
+    diff::{colorize, unified_diff},
+    error::AutoHeaderError,
+    file_diff,
+    git::{changed_files, file_commits},
+    header::{
+        decode_lines, encode_lines, fill_template, has_birthtime, post_process, render_token,
+        template_encoding, FileDates, BUILTIN_TOKENS,
+    },
+    locales::is_supported_locale,
+    logger::FileLog,
+    migrate::migrate,
+    paths::{directory_files, expand_path, in_git_dir},
+    process_content, process_file, relative_path, rename_author,
+    report::{dry_run_summary, on_complete, write_report, ReportEntry, RunSummary},
+    Action, Options, Outcome,
+};
 use chrono::{DateTime, Local};
-use clap::Parser;
-use detect_lang::from_path;
-use serde::Deserialize;
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use notify::{
+    event::{EventKind, ModifyKind},
+    Event, RecursiveMode, Watcher,
+};
 use std::{
+    collections::BTreeSet,
     env,
-    error::Error,
-    fs::{self, File},
-    io::{Read, Write},
-    path::Path,
-    str,
+    fmt::Display,
+    fs,
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc,
+    time::Duration,
 };
 
-use crate::licenses::get_license;
-
-/// Global configuration for the auto-header.
-#[derive(Debug, Deserialize)]
-struct Config {
-    /// Controls wether or not a header should be created if absent.
-    #[serde(default)]
-    create: bool,
-    /// Controls wether or not an existing header should be updated.
-    #[serde(default)]
-    update: bool,
-    /// Determines if we should use the default template for any language
-    /// with no specific template defined.
-    #[serde(default)]
-    language_strict: bool,
-    /// Default locale to use for date formatting
-    #[serde(default = "default_locale")]
-    locale: String,
-    /// Data used to fill the templates (names, mail addresses, *etc.*).
-    data: ConfigData,
-    /// Default template (fall back if no language specific one exists).
-    /// It will also serve to fill in the blanks left in language specific
-    /// templates.
-    default: Template,
-    /// Language specific templates.
-    language: Option<Vec<Template>>,
-    /// Projects configurations.
-    project: Option<Vec<Project>>,
+/// Application command line’s arguments.
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Path of the file to update, or of a directory whose files are updated recursively (`-` to
+    /// read from the standard input and write to the standard output)
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["print_config_path", "config_migrate", "init_project", "validate_config", "print_tokens", "git_modified", "git_staged", "watch", "config_test", "stdin"]
+    )]
+    path: Option<String>,
+    /// Read the content from the standard input and write it to the standard output, like `--path -`
+    #[arg(long, default_value_t = false, conflicts_with = "path")]
+    stdin: bool,
+    /// With a directory as --path, maximum depth of the processed files (1 for the files of the
+    /// directory only)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_depth: Option<u64>,
+    /// Configuration file (by default, the closest auto-header.toml above --path, or else the
+    /// user’s configuration file)
+    #[arg(short, long)]
+    config: Option<String>,
+    /// Configuration given as a TOML string, instead of a configuration file
+    #[arg(long, value_name = "TOML", conflicts_with_all = ["config", "print_config_path", "config_migrate", "init_project"])]
+    config_inline: Option<String>,
+    /// Only update the existing headers, never creating any, whatever the configuration
+    #[arg(short, long, default_value_t = false, conflicts_with = "no_update")]
+    update_only: bool,
+    /// Language of the file (or its extension), bypassing its detection from the extension
+    #[arg(long)]
+    language: Option<String>,
+    /// Path used to process the content read from the standard input (project, language, `#file_relative_path`)
+    #[arg(long, visible_alias = "path-hint")]
+    stdin_filename: Option<String>,
+    /// Name of the template to use, bypassing the language based selection
+    #[arg(long)]
+    template_name: Option<String>,
+    /// Recreate headers that are damaged, or written with another comment syntax, instead of skipping them
+    #[arg(short, long, default_value_t = false)]
+    force: bool,
+    /// Print the path of the configuration file that would be used, then exit
+    #[arg(long, default_value_t = false)]
+    print_config_path: bool,
+    /// Upgrade the configuration file to the current format and print it, then exit
+    #[arg(long, default_value_t = false)]
+    config_migrate: bool,
+    /// With --config-migrate, overwrite the configuration file (keeping a `.bak` backup)
+    #[arg(long, default_value_t = false, requires = "config_migrate")]
+    in_place: bool,
+    /// Print nothing on success, only errors (on the standard error)
+    #[arg(short, long, default_value_t = false)]
+    quiet: bool,
+    /// Check that every token of the templates can be filled with the configuration’s data, then exit
+    #[arg(long, default_value_t = false)]
+    validate_config: bool,
+    /// Print the tokens available in the templates with their description (and their value for
+    /// --path), then exit
+    #[arg(long, default_value_t = false)]
+    print_tokens: bool,
+    /// Register the current directory as a project in the configuration file, then exit
+    #[arg(long, default_value_t = false)]
+    init_project: bool,
+    /// With --init-project, name of the new project
+    #[arg(long, requires = "init_project")]
+    name: Option<String>,
+    /// Replace an author’s name or mail address in existing headers, instead of creating or updating them
+    #[arg(long, default_value_t = false, requires_all = ["from", "to"])]
+    rename_author: bool,
+    /// With --rename-author, name or mail address to replace
+    #[arg(long, requires = "rename_author")]
+    from: Option<String>,
+    /// With --rename-author, new name or mail address
+    #[arg(long, requires = "rename_author")]
+    to: Option<String>,
+    /// Date written in the headers as the current one, for reproducible headers: RFC 3339 date,
+    /// UNIX timestamp, or `env` to use `SOURCE_DATE_EPOCH` (the files’ modification times are
+    /// still the current date)
+    #[arg(long, value_parser = parse_timestamp_source)]
+    timestamp_source: Option<DateTime<Local>>,
+    /// Write what was done to the files in this report file (`.json` or `.csv`)
+    #[arg(long)]
+    report_file: Option<String>,
+    /// Temporarily make read-only files writable to update their header
+    #[arg(long, default_value_t = false)]
+    chmod: bool,
+    /// Never create headers during this run, whatever the configuration
+    #[arg(long, default_value_t = false)]
+    no_create: bool,
+    /// Never update headers during this run, whatever the configuration
+    #[arg(long, default_value_t = false)]
+    no_update: bool,
+    /// Write the processed files in this directory instead of modifying them in place
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Print the changes that would be made as a unified diff, without modifying the file
+    #[arg(long, default_value_t = false, conflicts_with = "rename_author")]
+    diff: bool,
+    /// Like --diff, but exit with 1 if there are changes (0 otherwise), like `git diff --exit-code`
+    #[arg(long, default_value_t = false, conflicts_with = "rename_author")]
+    diff_exit_code: bool,
+    /// Write the changes that would be made in this patch file (applicable with `git apply`), without modifying the file
+    #[arg(long, value_name = "FILE", conflicts_with = "rename_author")]
+    patch_out: Option<String>,
+    /// TOML file mapping extensions or file names to languages (`h = "cpp"`), completing the configuration’s `extensions`
+    #[arg(long, value_name = "FILE")]
+    language_map: Option<String>,
+    /// Process the files modified in the working tree of the current git repository (untracked ones included), instead of --path
+    #[arg(long, default_value_t = false, conflicts_with = "path")]
+    git_modified: bool,
+    /// Process the files staged in the index of the current git repository, instead of --path
+    #[arg(long, default_value_t = false, conflicts_with = "path")]
+    git_staged: bool,
+    /// Check that the headers are up to date, without modifying the files: exit with 1 if one isn’t
+    /// (see the configuration’s `max_age_days`)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["rename_author", "diff", "diff_exit_code", "patch_out"])]
+    check: bool,
+    /// Never color the output (colors are also disabled by a non-empty `NO_COLOR` environment
+    /// variable, and when the output isn’t a terminal)
+    #[arg(long, default_value_t = false)]
+    no_color: bool,
+    /// Watch a directory, creating and updating the headers of its files as they’re saved, until
+    /// interrupted
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["path", "git_modified", "git_staged", "diff", "check", "dry_run", "patch_out", "rename_author"])]
+    watch: Option<String>,
+    /// Render the header of a sample file and compare it with an expected header file: print their
+    /// differences and exit with 1 if they don’t match (dates are reproducible with
+    /// --timestamp-source)
+    #[arg(long, num_args = 2, value_names = ["SAMPLE", "EXPECTED"], conflicts_with_all = ["path", "git_modified", "git_staged", "watch"])]
+    config_test: Option<Vec<String>>,
+    /// Explain precisely why each skipped file was skipped (missing project or template,
+    /// creation or update policy and where it’s set, …)
+    #[arg(long, default_value_t = false)]
+    explain_skip: bool,
+    /// Only check that every targeted file can be read and written, reporting those that can’t,
+    /// without modifying anything: exit with 1 if one can’t
+    #[arg(long, default_value_t = false, conflicts_with_all = ["watch", "check", "diff", "dry_run", "patch_out"])]
+    preflight: bool,
+    /// Modify the files, and print the changes made to them as a unified diff (messages are then
+    /// written on the standard error)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["diff", "diff_exit_code", "patch_out", "check", "dry_run", "output", "watch"])]
+    tee: bool,
+    /// Print what would be done to the file, without modifying it
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+    /// With --dry-run, group the files by action (colored when the output is a terminal)
+    #[arg(long, default_value_t = false, requires = "dry_run")]
+    group: bool,
+    /// With --dry-run or --check, only list (and report) the files that would be modified, or
+    /// that couldn’t be processed
+    #[arg(long, default_value_t = false)]
+    changed_only: bool,
+    /// When updating a header, only rewrite the bytes that changed instead of the whole file
+    #[arg(long, default_value_t = false)]
+    only_changed_lines: bool,
+    /// Rewrite the comment prefix of every line of the existing headers to be exactly the
+    /// template’s (after a change of its spacing for example), not only the tracked lines
+    #[arg(long, default_value_t = false)]
+    normalize_prefix: bool,
+    /// Fail if the file system doesn’t record the file’s creation date, instead of warning and using its modification date
+    #[arg(long, default_value_t = false)]
+    fail_on_missing_birthtime: bool,
+    /// Define a custom token for this run (`--define gen_tool=protoc` fills `#gen_tool`)
+    #[arg(short, long = "define", value_name = "KEY=VALUE", value_parser = parse_define)]
+    defines: Vec<(String, String)>,
 }
 
-fn default_locale() -> String {
-    String::from("en")
+/// Path designating the standard input and output.
+const STDIN: &str = "-";
+
+/// Parses a `KEY=VALUE` token definition from the command line.
+fn parse_define(define: &str) -> Result<(String, String), String> {
+    define
+        .split_once('=')
+        .map(|(key, value)| (key.trim_start_matches('#').to_owned(), value.to_owned()))
+        .ok_or(format!("invalid definition {}: expected KEY=VALUE", define))
 }
 
-/// Data used to fill the templates.
-#[derive(Clone, Debug, Deserialize)]
-struct ConfigData {
-    /// Name of the author.
-    author: Option<String>,
-    /// Mail address of the author.
-    author_mail: Option<String>,
-    /// Copyright holders if any.
-    cp_holders: Option<String>,
-    /// Copyright notice.
-    copyright_notice: Option<String>,
+/// Parses the source of the date used as the current one: `env` for the
+/// `SOURCE_DATE_EPOCH` environment variable, or a date.
+fn parse_timestamp_source(source: &str) -> Result<DateTime<Local>, String> {
+    if source != "env" {
+        return parse_timestamp(source);
+    }
+    let epoch =
+        env::var("SOURCE_DATE_EPOCH").map_err(|_| String::from("SOURCE_DATE_EPOCH is not set"))?;
+    parse_timestamp(&epoch).map_err(|err| format!("SOURCE_DATE_EPOCH: {}", err))
 }
 
-impl ConfigData {
-    /// Merge a given `ConfigData` with the default one.
-    ///
-    /// # Arguments
-    /// * `default` - `ConfigData` by default, used to fill
-    /// unspecified values.
-    ///
-    /// # Example
-    /// ```
-    /// let global_config = toml::from_str(fs::read_to_string(args.config)?.as_str())?;
-    /// let mut project = find_project(&global_config, "./src/main.rs").unwrap();
-    /// project.data = if let Some(data) = project.data {
-    ///     Some(data.merge(&config.data))
-    /// } else {
-    ///     Some(config.data.clone())
-    /// };
-    /// ```
-    fn merge(self, default: &ConfigData) -> Self {
-        Self {
-            author: Some(self.author.unwrap_or(default.author.clone().unwrap())),
-            author_mail: Some(
-                self.author_mail
-                    .unwrap_or(default.author_mail.clone().unwrap()),
-            ),
-            cp_holders: Some(
-                self.cp_holders
-                    .unwrap_or(default.cp_holders.clone().unwrap()),
-            ),
-            copyright_notice: Some(
-                self.copyright_notice
-                    .unwrap_or(default.copyright_notice.clone().unwrap()),
-            ),
+/// Parses a date given as RFC 3339 date or as UNIX timestamp.
+fn parse_timestamp(timestamp: &str) -> Result<DateTime<Local>, String> {
+    let date = match timestamp.trim().parse::<i64>() {
+        Ok(seconds) => DateTime::from_timestamp(seconds, 0),
+        Err(_) => DateTime::parse_from_rfc3339(timestamp.trim())
+            .ok()
+            .map(|date| date.to_utc()),
+    };
+    date.map(|date| date.with_timezone(&Local)).ok_or(format!(
+        "invalid timestamp {}: expected an RFC 3339 date or a UNIX timestamp",
+        timestamp
+    ))
+}
+
+fn main() -> ExitCode {
+    let mut args = Args::parse();
+    args.diff |= args.diff_exit_code;
+    if args.stdin {
+        args.path = Some(String::from(STDIN));
+    }
+    if args.print_config_path {
+        return match config_file(&args) {
+            Ok(path) => {
+                println!("{}", path);
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                FileLog::new().color(!args.no_color).error(err.to_string());
+                exit_code(&err)
+            }
+        };
+    }
+    if args.changed_only && !(args.dry_run || args.check) {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--changed-only requires --dry-run or --check",
+            )
+            .exit();
+    }
+    let stdin = args.path.as_deref() == Some(STDIN);
+    if stdin
+        && args.language.is_none()
+        && args.stdin_filename.is_none()
+        && args.template_name.is_none()
+    {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--path - and --stdin require --language, --stdin-filename or --template-name",
+            )
+            .exit();
+    }
+    if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        args.no_color = true;
+    }
+    let new_log = || {
+        if stdin || args.diff || args.tee {
+            FileLog::stderr()
+        } else {
+            FileLog::new()
         }
+        .quiet(args.quiet)
+        .color(!args.no_color)
+    };
+    let mut log = new_log();
+    let command = if args.config_migrate {
+        Some(migrate_config(&args, &mut log))
+    } else if args.init_project {
+        Some(init_project(&args, &mut log))
+    } else if args.validate_config {
+        Some(validate_config(&args, &mut log))
+    } else if args.print_tokens {
+        Some(print_tokens(&args, &mut log))
+    } else if let Some(files) = &args.config_test {
+        Some(config_test(&args, &files[0], &files[1], &mut log))
+    } else {
+        args.watch.as_ref().map(|dir| watch(&args, dir))
+    };
+    if let Some(result) = command {
+        return match result {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                log.error(err.to_string());
+                exit_code(&err)
+            }
+        };
     }
-}
 
-/// Header template, global or language specific.
-#[derive(Clone, Debug, Deserialize)]
-struct Template {
-    /// Language for which the template applies ("*" for default).
-    name: String,
-    /// String put at the beginning of every line in the header.
-    prefix: Option<String>,
-    /// Strings added before the header (such as shebangs for example).
-    before: Option<Vec<String>>,
-    /// Strings added after the header.
-    after: Option<Vec<String>>,
-    /// Value of the header template.
-    template: Option<String>,
-    /// Lines that should be updated when an existing header is updated.
-    track_changes: Option<Vec<String>>,
+    // Directories that couldn’t be walked are reported with the failed files.
+    let mut entries = Vec::new();
+    let mut paths = if args.git_modified || args.git_staged {
+        match changed_files(args.git_modified, args.git_staged) {
+            Ok(paths) => paths,
+            Err(err) => {
+                log.error(err.to_string());
+                return exit_code(&err);
+            }
+        }
+    } else {
+        match args.path.clone().unwrap_or_default() {
+            path if Path::new(&path).is_dir() => {
+                let max_depth = args.max_depth.map(|depth| depth as usize);
+                let (paths, errors) = directory_files(&path, max_depth);
+                for (dir, err) in errors {
+                    log.error(err.to_string());
+                    entries.push(ReportEntry {
+                        error: Some(err.to_string()),
+                        ..ReportEntry::new(&dir)
+                    });
+                }
+                paths
+            }
+            path => vec![path],
+        }
+    };
+    if args.preflight {
+        return preflight(&args, &paths, &mut log);
+    }
+    // The configuration is parsed once for all the files.
+    let config = match run_config(&args) {
+        Ok(config) => config,
+        Err(err) => {
+            log.error(err.to_string());
+            return exit_code(&err);
+        }
+    };
+    if walking(&args) {
+        // The configuration file itself is never processed.
+        if let Ok(config) = config_file(&args) {
+            paths.retain(|path| !same_file(path, &config));
+        }
+    }
+    drop(log);
+    let mut code = if entries.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    };
+    let batch = paths.len() + entries.len() > 1;
+    let mut patch = String::new();
+    for path in paths {
+        let mut log = new_log();
+        let mut entry = ReportEntry::new(&path);
+        // A failing file is reported, and the others are still processed.
+        if let Err(err) = run(&args, &config, &path, &mut log, &mut entry, &mut patch) {
+            let message = err.to_string();
+            // Errors that don’t name the file are given its path.
+            if batch && !message.contains(&path) {
+                log.error(format!("{}: {}", path, message));
+            } else {
+                log.error(message.clone());
+            }
+            entry.error = Some(message);
+            code = exit_code(&err);
+        }
+        entries.push(entry);
+    }
+    if batch {
+        failures_summary(&entries, &mut new_log());
+    }
+    if walking(&args) && !args.dry_run {
+        let summary = RunSummary::new(&entries);
+        new_log().info(format!(
+            "{} header(s) created, {} updated, {} up to date, {} skipped, {} failed.",
+            summary.created,
+            summary.updated + summary.recreated + summary.replaced,
+            summary.up_to_date,
+            summary.skipped,
+            summary.failed
+        ));
+    }
+
+    if args.changed_only {
+        entries.retain(|entry| entry.changed() || entry.error.is_some());
+    }
+    let mut log = new_log();
+    if let Some(patch_out) = &args.patch_out {
+        if let Err(err) = fs::write(patch_out, patch).map_err(AutoHeaderError::write(patch_out)) {
+            log.error(err.to_string());
+            return exit_code(&err);
+        }
+    }
+    if args.dry_run {
+        print!(
+            "{}",
+            dry_run_summary(
+                &entries,
+                args.group,
+                !args.no_color && io::stdout().is_terminal()
+            )
+        );
+    }
+    if let Some(report) = &args.report_file {
+        if let Err(err) = write_report(report, &entries) {
+            log.error(err.to_string());
+            return exit_code(&err);
+        }
+    }
+    if !(stdin || args.dry_run || args.diff || args.check || args.patch_out.is_some()) {
+        if let Some(command) = &config.on_complete {
+            if let Err(err) = on_complete(command, &entries) {
+                log.error(err.to_string());
+                return exit_code(&err);
+            }
+        }
+    }
+    if args.diff_exit_code && entries.iter().any(ReportEntry::changed) {
+        return ExitCode::FAILURE;
+    }
+    code
 }
 
-impl Template {
-    /// Merge the current template with the one by default.
-    ///
-    /// # Arguments
-    /// * `default` - `Template` by default, which will be used to fill any
-    /// missing values in the language specific template.
-    ///
-    /// # Example
-    /// ```
-    /// let global_config = toml::from_str(fs::read_to_string(args.config)?.as_str())?;
-    /// let language = String::from("rs");
-    /// let language_config = get_language_config(&global_config, &language);
-    /// let language_config = language_config.merge(&global_config.default);
-    /// ```
-    fn merge(self, default: &Template) -> Self {
-        Self {
-            name: self.name,
-            prefix: Some(self.prefix.unwrap_or(default.prefix.clone().unwrap())),
-            before: Some(self.before.unwrap_or(default.before.clone().unwrap())),
-            after: Some(self.after.clone().unwrap_or(default.after.clone().unwrap())),
-            template: Some(self.template.unwrap_or(default.template.clone().unwrap())),
-            track_changes: Some(
-                self.track_changes
-                    .unwrap_or(default.track_changes.clone().unwrap()),
-            ),
+/// Reports, at the end of a run on several files, those that couldn’t be
+/// processed and why.
+///
+/// # Arguments
+/// * `entries` - Entries of the processed files.
+/// * `log` - Log of the messages.
+fn failures_summary(entries: &[ReportEntry], log: &mut FileLog) {
+    let failed: Vec<&ReportEntry> = entries.iter().filter(|e| e.error.is_some()).collect();
+    if failed.is_empty() {
+        return;
+    }
+    log.error(format!(
+        "{} of {} file(s) couldn’t be processed:",
+        failed.len(),
+        entries.len()
+    ));
+    for entry in failed {
+        let message = entry.error.as_deref().unwrap_or_default();
+        if message.contains(&entry.path) {
+            log.error(format!("  {}", message));
+        } else {
+            log.error(format!("  {}: {}", entry.path, message));
         }
     }
 }
 
-/// Project configuration.
-#[derive(Clone, Debug, Deserialize)]
-struct Project {
-    /// Root path of the project.
-    root: String,
-    /// Name of the project.
-    name: Option<String>,
-    /// Controls wether or not an existing header should be updated for this project.
-    create: Option<bool>,
-    /// Controls wether or not an existing header should be updated for this project.
-    update: Option<bool>,
-    /// Locale to format the date with on this project.
-    locale: Option<String>,
-    /// Data specific to this project.
-    data: Option<ConfigData>,
+/// Checks that the targeted files can be opened for reading and writing (and
+/// aren’t read-only, unless `--chmod` is given), without modifying them, and
+/// reports those that can’t.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `paths` - Paths of the targeted files.
+/// * `log` - Log of the messages.
+fn preflight(args: &Args, paths: &[String], log: &mut FileLog) -> ExitCode {
+    let problems = paths
+        .iter()
+        .filter(|path| path.as_str() != STDIN)
+        .filter_map(|path| {
+            let file = fs::OpenOptions::new().read(true).open(path);
+            if let Err(err) = file {
+                return Some(AutoHeaderError::read(path)(err));
+            }
+            // Read-only files are refused, even when their owner could write them.
+            let readonly = fs::metadata(path).is_ok_and(|m| m.permissions().readonly());
+            if readonly && !args.chmod {
+                return Some(AutoHeaderError::ReadOnly(path.clone()));
+            }
+            // Opening for writing doesn’t truncate the file, nor change its dates.
+            let file = fs::OpenOptions::new().write(true).open(path);
+            file.err().map(AutoHeaderError::write(path))
+        })
+        .inspect(|err| log.error(err.to_string()))
+        .count();
+    log.info(format!(
+        "{} file(s) checked, {} can’t be read or written.",
+        paths.len(),
+        problems
+    ));
+    if problems == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
 
-/// Application command line’s arguments.
-#[derive(Debug, Parser)]
-#[command(author, version, about, long_about = None)]
-struct Args {
-    /// Path of the file to update
-    #[arg(short, long)]
-    path: String,
-    #[arg(short, long, default_value_t = format!("{}/auto-header/configuration.toml", env::var("XDG_CONFIG_HOME").unwrap()))]
-    config: String,
-    #[arg(short, long, default_value_t = false)]
-    update_only: bool,
+/// Loads the configuration given on the command line (inline, or its file).
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+fn load_config(args: &Args) -> Result<Config, AutoHeaderError> {
+    if let Some(content) = &args.config_inline {
+        return Config::load_inline(content);
+    }
+    let target = args.path.as_deref().unwrap_or(".");
+    Config::load(args.config.as_deref().map(Path::new), Path::new(target))
+}
+
+/// Gets the path of the configuration file used: the one given on the command
+/// line, or else the one found for the targeted path.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+fn config_file(args: &Args) -> Result<String, AutoHeaderError> {
+    let target = args.path.as_deref().unwrap_or(".");
+    config_path(args.config.as_deref().map(Path::new), Path::new(target))
+}
+
+/// Loads the configuration the files are processed with: the one given on the
+/// command line, with the extensions given by `--language-map`.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+fn run_config(args: &Args) -> Result<Config, AutoHeaderError> {
+    let config = load_config(args)?;
+    match &args.language_map {
+        Some(map) => config.with_language_map(map),
+        None => Ok(config),
+    }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args = Args::parse();
-    if !Path::new(&args.path).exists() {
-        println!("File {} does not exist.", args.path);
+/// Gets the language given by `--language`, which may be the extension of the
+/// files of the language (`rs`) when no template applies to it.
+///
+/// # Arguments
+/// * `config` - Configuration of the run.
+/// * `language` - Language given on the command line.
+fn language_name(config: &Config, language: &str) -> String {
+    if get_language_config(config, language).is_some_and(|t| t.applies_to(language)) {
+        return language.to_owned();
+    }
+    match get_language(config, &format!("file.{}", language)) {
+        name if name == "*" => language.to_owned(),
+        name => name,
+    }
+}
+
+/// Checks whether `--path` is a directory, whose files are all processed.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+fn walking(args: &Args) -> bool {
+    args.path
+        .as_deref()
+        .is_some_and(|path| Path::new(path).is_dir())
+}
+
+/// Checks whether two paths lead to the same file.
+///
+/// # Arguments
+/// * `path` - First path.
+/// * `other` - Second path.
+fn same_file(path: &str, other: &str) -> bool {
+    match (fs::canonicalize(path), fs::canonicalize(expand_path(other))) {
+        (Ok(path), Ok(other)) => path == other,
+        _ => false,
+    }
+}
+
+/// Exit code of the application for a given error.
+///
+/// # Arguments
+/// * `err` - Error that stopped the processing.
+fn exit_code(err: &AutoHeaderError) -> ExitCode {
+    match err {
+        AutoHeaderError::ConfigNotFound(_)
+        | AutoHeaderError::ConfigParse(_)
+        | AutoHeaderError::InvalidConfig { .. }
+        | AutoHeaderError::ConfigFormat(_)
+        | AutoHeaderError::MissingField(_)
+        | AutoHeaderError::NoTemplate(_)
+        | AutoHeaderError::IncludeCycle(_)
+        | AutoHeaderError::UnresolvedTokens(_)
+        | AutoHeaderError::DuplicateProject(_)
+        | AutoHeaderError::FileNotFound(_) => ExitCode::from(2),
+        _ => ExitCode::FAILURE,
+    }
+}
+
+/// Upgrades the configuration file to the current format, printing the result
+/// or writing it in place.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `log` - Log of the messages.
+fn migrate_config(args: &Args, log: &mut FileLog) -> Result<(), AutoHeaderError> {
+    let path = config_file(args)?;
+    if !Path::new(&path).exists() {
+        return Err(AutoHeaderError::ConfigNotFound(path));
+    }
+    let content = fs::read_to_string(&path).map_err(AutoHeaderError::read(&path))?;
+    let (migrated, changes) = migrate(&content)?;
+    if !args.in_place {
+        println!("{}", migrated);
+        changes.iter().for_each(|change| eprintln!("{}", change));
         return Ok(());
     }
-    if !Path::new(&args.config).exists() {
-        println!("Configuration file {} does not exist.", args.config);
+    if changes.is_empty() {
+        log.info(format!("{} is already up to date.", path));
         return Ok(());
     }
-    let config: Config = match toml::from_str(fs::read_to_string(args.config)?.as_str()) {
-        Ok(config) => config,
-        Err(err) => {
-            println!("Error reading configuration file: {}", err);
-            return Ok(());
-        }
-    };
+    let backup = format!("{}.bak", path);
+    fs::write(&backup, &content).map_err(AutoHeaderError::write(&backup))?;
+    fs::write(&path, migrated).map_err(AutoHeaderError::write(&path))?;
+    changes.into_iter().for_each(|change| log.info(change));
+    log.info(format!("Previous configuration saved to {}.", backup));
+    Ok(())
+}
 
-    // Get the project’s configuration and check that we’re doing something with it.
-    let project = find_project(&config, &args.path);
-    let mut project = if let Some(project) = project {
-        if !project.create.unwrap_or(config.create) && !project.update.unwrap_or(config.update) {
-            println!("Project’s configuration forbids creation and update of headers: exiting.");
-            return Ok(());
+/// Checks that the templates of the configuration file can be filled,
+/// reporting every unresolved token.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `log` - Log of the messages.
+fn validate_config(args: &Args, log: &mut FileLog) -> Result<(), AutoHeaderError> {
+    let config = load_config(args)?;
+    for root in config.duplicate_roots() {
+        log.info(format!(
+            "Several projects have the root {}, only the first one is used.",
+            root
+        ));
+    }
+    let problems = config.unresolved_tokens();
+    if problems.is_empty() {
+        match args.config_inline {
+            Some(_) => log.info("The inline configuration is valid."),
+            None => log.info(format!("{} is valid.", config_file(args)?)),
         }
-        project
-    } else {
-        println!("No configuration found for file {}. Exiting.", args.path);
         return Ok(());
+    }
+    let count = problems.len();
+    problems.into_iter().for_each(|problem| log.error(problem));
+    Err(AutoHeaderError::UnresolvedTokens(count))
+}
+
+/// Prints the tokens available in the templates: the built-in ones and the
+/// custom ones, with the value they would have in the header of the file if
+/// one is given.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `log` - Log of the messages.
+fn print_tokens(args: &Args, log: &mut FileLog) -> Result<(), AutoHeaderError> {
+    let config = load_config(args)?;
+    let target = match args.path.as_deref().filter(|path| *path != STDIN) {
+        Some(path) => prepare(args, &config, path, log, &mut ReportEntry::new(path))?,
+        None => None,
+    };
+    let dates = match args.path.as_deref().filter(|path| Path::new(path).exists()) {
+        Some(path) => FileDates::from_file(path)?,
+        None => FileDates::now(),
+    };
+    let dates = FileDates {
+        now: args.timestamp_source.unwrap_or(dates.now),
+        ..dates
     };
+    let mut custom = match &target {
+        Some((project, _, _)) => project.data.as_ref().and_then(|data| data.tokens.clone()),
+        None => config.data.tokens.clone(),
+    }
+    .unwrap_or_default();
+    custom.extend(args.defines.iter().cloned());
 
-    project.data = Some(if let Some(data) = project.data {
-        data.merge(&config.data)
-    } else {
-        config.data.clone()
-    });
+    let tokens = BUILTIN_TOKENS
+        .iter()
+        .map(|(token, description)| (token.to_string(), description.to_string()))
+        .chain(custom.into_keys().map(|token| {
+            let description = if args.defines.iter().any(|(key, _)| *key == token) {
+                "Custom token (--define)"
+            } else {
+                "Custom token (data.tokens)"
+            };
+            (token, description.to_owned())
+        }));
+    for (token, description) in tokens {
+        println!("#{:<20} {}", token, description);
+        let (Some(path), Some((project, template, _))) = (&args.path, &target) else {
+            continue;
+        };
+        match render_token(&token, template, project, path, &dates)? {
+            Some(value) => println!("{:<21} = {}", "", value),
+            None => println!("{:<21} = (computed when the header is written)", ""),
+        }
+    }
+    Ok(())
+}
 
-    if project
-        .data
-        .as_ref()
-        .is_some_and(|data| data.copyright_notice.is_some())
+/// Renders the header of a sample file as it would be created, and compares it
+/// with the expected one, printing their differences as a unified diff. The
+/// blank lines ending the headers are ignored.
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `sample` - Path of the sample file.
+/// * `expected` - Path of the file holding the expected header.
+/// * `log` - Log of the messages.
+fn config_test(
+    args: &Args,
+    sample: &str,
+    expected: &str,
+    log: &mut FileLog,
+) -> Result<(), AutoHeaderError> {
+    if let Some(path) = [sample, expected]
+        .into_iter()
+        .find(|path| !Path::new(path).exists())
     {
-        let mut data = project.data.clone().unwrap();
-        data.copyright_notice = Some(get_license(&data.copyright_notice.unwrap()).to_owned());
-        project.data = Some(data);
+        return Err(AutoHeaderError::FileNotFound(path.to_owned()));
     }
-
-    // Get the language for the target file.
-    let language = get_language(&args.path);
-    let lang_conf = match get_language_config(&config, &language) {
-        Some(lang_conf) => lang_conf.merge(&config.default),
-        None => {
-            println!(
-                "No configuration found for file {} (language {}). Exiting.",
-                args.path, language
-            );
-            return Ok(());
-        }
+    let config = run_config(args)?;
+    let Some((project, template, _)) =
+        prepare(args, &config, sample, log, &mut ReportEntry::new(sample))?
+    else {
+        return Err(AutoHeaderError::NoProject(sample.to_owned()));
     };
-    // Build the header.
-    let header = fill_template(&lang_conf, &project, &args.path, &project.root);
-    // Check if it’s an update or creation, and update / adds the header in the file.
-    let header_present = check_header_exists(&args.path, &header, &lang_conf);
-    if header_present && config.update {
-        match update_header(&args.path, &header, &lang_conf) {
-            Ok(_) => (),
-            Err(err) => println!("Failed to update header: {}", err),
-        }
-    } else if !header_present && config.create {
-        match write_header(&args.path, &header) {
-            Ok(_) => (),
-            Err(err) => println!("Failed to write header: {}", err),
+    let dates = match args.timestamp_source {
+        Some(timestamp) => FileDates::at(timestamp),
+        None => FileDates::from_file(sample)?.touched(),
+    };
+    let header = fill_template(&template, &project, sample, &project.root, &dates)?;
+    let trimmed = |mut lines: Vec<String>| {
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
         }
+        lines.push(String::new());
+        lines
+    };
+    let header = trimmed(post_process(header, &template)?);
+    let wanted = trimmed(decode_lines(
+        expected,
+        &fs::read(expected).map_err(AutoHeaderError::read(expected))?,
+        template_encoding(&template)?,
+    )?);
+
+    let diff = unified_diff(expected, &wanted, &header);
+    if diff.is_empty() {
+        log.info(format!(
+            "Header generated for {} matches {}.",
+            sample, expected
+        ));
+        return Ok(());
+    }
+    if !args.no_color && io::stdout().is_terminal() {
+        print!("{}", colorize(&diff));
     } else {
-        println!(
-            "nothing to do: header exists = {} with configuration create = {} and update = {}",
-            header_present, config.create, config.update
-        );
+        print!("{}", diff);
     }
-    Ok(())
+    Err(AutoHeaderError::UnexpectedHeader {
+        path: sample.to_owned(),
+        expected: expected.to_owned(),
+    })
 }
 
-/// Get the file’s language from the extension.
+/// Appends a project rooted in the current directory to the configuration
+/// file, keeping its existing content untouched.
 ///
 /// # Arguments
-/// * `path` - path to the file to format.
-///
-/// # Example
-/// ```
-/// let lang = get_language("./src/main.rs");
-/// ```
-fn get_language(path: &str) -> String {
-    String::from(match from_path(path) {
-        Some(lang) => lang.id(),
-        None => "*",
-    })
+/// * `args` - Command line arguments.
+/// * `log` - Log of the messages.
+fn init_project(args: &Args, log: &mut FileLog) -> Result<(), AutoHeaderError> {
+    let config = load_config(args)?;
+    let path = config_file(args)?;
+    let content = fs::read_to_string(&path).map_err(AutoHeaderError::read(&path))?;
+    let root = env::current_dir()
+        .map_err(AutoHeaderError::read("."))?
+        .to_string_lossy()
+        .into_owned();
+    if config.project.iter().flatten().any(|p| p.root == root) {
+        log.info(format!("{} is already a project of {}.", root, path));
+        return Ok(());
+    }
+
+    let mut entry = String::from(if content.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    });
+    entry.push_str("[[project]]\n");
+    entry.push_str(&format!("root = {}\n", toml::Value::String(root.clone())));
+    if let Some(name) = &args.name {
+        entry.push_str(&format!("name = {}\n", toml::Value::String(name.clone())));
+    }
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(entry.as_bytes()))
+        .map_err(AutoHeaderError::write(&path))?;
+    log.info(format!("Project {} added to {}.", root, path));
+    Ok(())
 }
 
-/// Get the language specific configuration.
+/// Time without any change to the watched files after which they’re processed,
+/// so that rapid saves are processed once.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watches a directory, creating and updating the headers of its files when
+/// they’re created or modified. Only the errors and the modified files are
+/// reported.
 ///
 /// # Arguments
-/// * `config` - Global configuration.
-/// * `language` - Language for which we want the configuration.
-///
-/// # Example
-/// ```
-/// let config: Config = toml::from_str(fs::read_to_string(args.config)?.as_str())?;
-/// let language = get_language(&args.path);
-/// let lang_conf = get_language_config(&config, &language);
-/// ```
-fn get_language_config(config: &Config, language: &str) -> Option<Template> {
-    if config.language.is_none() {
-        return Some(config.default.clone());
+/// * `args` - Command line arguments.
+/// * `dir` - Directory to watch.
+fn watch(args: &Args, dir: &str) -> Result<(), AutoHeaderError> {
+    let failure = |err: notify::Error| AutoHeaderError::Watch {
+        path: dir.to_owned(),
+        reason: err.to_string(),
     };
-    let res = config
-        .language
-        .as_ref()
-        .unwrap()
-        .iter()
-        .find(|t| t.name == language);
-    match res {
-        Some(res) => Some(res.clone()),
-        None => {
-            if !config.language_strict {
-                Some(config.default.clone())
-            } else {
-                None
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(failure)?;
+    watcher
+        .watch(Path::new(dir), RecursiveMode::Recursive)
+        .map_err(failure)?;
+    let mut log = FileLog::new().quiet(args.quiet).color(!args.no_color);
+    log.info(format!("Watching {} for changes (Ctrl+C to stop).", dir));
+    log.flush();
+
+    let current = env::current_dir().map_err(AutoHeaderError::read("."))?;
+    while let Ok(event) = receiver.recv() {
+        let mut paths = BTreeSet::new();
+        changed_paths(event, &mut paths);
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            changed_paths(event, &mut paths);
+        }
+        for path in paths {
+            let path = path
+                .strip_prefix(&current)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .into_owned();
+            // Files to which nothing applies are frequent (temporary files of
+            // the editors): only the outcome is reported.
+            let mut file_log = FileLog::new().quiet(true).color(!args.no_color);
+            let mut entry = ReportEntry::new(&path);
+            // The configuration is read again, so that its changes are applied.
+            let result = run_config(args).and_then(|config| {
+                run(
+                    args,
+                    &config,
+                    &path,
+                    &mut file_log,
+                    &mut entry,
+                    &mut String::new(),
+                )
+            });
+            match result {
+                Err(err) => file_log.error(err.to_string()),
+                Ok(()) if entry.changed() => {
+                    log.info(format!("{}: {}", path, entry.action.unwrap_or_default()))
+                }
+                Ok(()) => (),
             }
+            file_log.flush();
+            log.flush();
         }
     }
+    Ok(())
 }
 
-/// Given the path of the considered file, gets the project’s configuration
-/// if it exists.
+/// Adds the files created or modified by a file system event to a set.
 ///
 /// # Arguments
-/// * `config` - Global configuration.
-/// * `path` - Path to the file for which to create or update the header.
+/// * `event` - Event received from the watcher.
+/// * `paths` - Paths of the modified files.
+fn changed_paths(event: notify::Result<Event>, paths: &mut BTreeSet<PathBuf>) {
+    let Ok(event) = event else {
+        return;
+    };
+    if matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+    ) {
+        paths.extend(
+            event
+                .paths
+                .into_iter()
+                .filter(|path| path.is_file() && !in_git_dir(&path.to_string_lossy())),
+        );
+    }
+}
+
+/// Creates or updates the header of a file.
 ///
-/// # Example
-/// ```
-/// let config: Config = toml::from_str(fs::read_to_string(args.config)?.as_str())?;
-/// let project = find_project(&config, "./src/main.rs");
-/// ```
-fn find_project(config: &Config, path: &str) -> Option<Project> {
-    if config.project.as_ref().unwrap_or(&Vec::new()).is_empty() {
-        return None;
-    }
-    let path = Path::new(&env::current_dir().unwrap()).join(path);
-    let mut path = Some(path.as_path());
-    while path.is_some() {
-        let project = config
-            .project
-            .as_ref()
-            .unwrap()
-            .iter()
-            .find(|p| Path::new(&p.root) == path.unwrap());
-        match project {
-            None => path = path.unwrap().parent(),
-            Some(res) => return Some(res.clone()),
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `config` - Configuration of the run.
+/// * `path` - Path of the file.
+/// * `log` - Log of the messages for the file.
+/// * `entry` - Report entry of the file.
+/// * `patch` - Patch of the run, completed with the changes to the file if
+///   they are written to a patch file.
+fn run(
+    args: &Args,
+    config: &Config,
+    path: &str,
+    log: &mut FileLog,
+    entry: &mut ReportEntry,
+    patch: &mut String,
+) -> Result<(), AutoHeaderError> {
+    let path = path.to_owned();
+    let stdin = path == STDIN;
+    if !stdin && !Path::new(&path).exists() {
+        return Err(AutoHeaderError::FileNotFound(path));
+    }
+    if !stdin && in_git_dir(&path) {
+        log.info(format!(
+            "{} is inside a .git directory: refusing to modify it.",
+            path
+        ));
+        explain_skip(
+            args,
+            log,
+            &path,
+            "the files of .git directories are never modified",
+        );
+        return Ok(());
+    }
+    if stdin {
+        let name = args.stdin_filename.clone().unwrap_or(path);
+        // The content would be written back unchanged, as if it had been processed.
+        if find_project(config, &name)?.is_none() {
+            return Err(match args.stdin_filename {
+                Some(_) => AutoHeaderError::NoProject(name),
+                None => AutoHeaderError::NoStdinProject,
+            });
+        }
+        let target = prepare(args, config, &name, log, entry)?;
+        if let Some(action) = process_stdin(args, &name, target, log)? {
+            entry.set_action(action);
+        }
+        return Ok(());
+    }
+    let Some((project, lang_conf, options)) = prepare(args, config, &path, log, entry)? else {
+        return Ok(());
+    };
+    let policy = policy_origin(args, config, &project, &lang_conf);
+    if args.check {
+        let action = check_file(&path, &project, &lang_conf, &options, config.max_age_days)?;
+        entry.set_action(action);
+        log_skipped(args, action, &options, log);
+        explain_action(args, log, &path, action, &options, &policy);
+        return Ok(());
+    }
+    if args.diff || args.patch_out.is_some() {
+        let (action, diff) = file_diff(&path, &project, &lang_conf, &options)?;
+        entry.set_action(action);
+        log_skipped(args, action, &options, log);
+        if args.diff && !args.no_color && io::stdout().is_terminal() {
+            print!("{}", colorize(&diff));
+        } else if args.diff {
+            print!("{}", diff);
+        }
+        if args.patch_out.is_some() {
+            patch.push_str(&diff);
+        }
+        return Ok(());
+    }
+    let read = || {
+        let bytes = fs::read(&path).map_err(AutoHeaderError::read(&path))?;
+        decode_lines(&path, &bytes, template_encoding(&lang_conf)?)
+    };
+    let before = if args.tee { Some(read()?) } else { None };
+    let result = match (&args.from, &args.to) {
+        (Some(from), Some(to)) if args.rename_author => {
+            rename_author(&path, &project, &lang_conf, &options, from, to)
+        }
+        _ => process_file(&path, &project, &lang_conf, &options),
+    };
+    let action = match result {
+        // A read-only file doesn’t fail the run: it’s skipped with a warning.
+        Err(err @ AutoHeaderError::ReadOnly(_)) => {
+            log.info(err.to_string());
+            explain_skip(args, log, &path, "it’s read-only, and --chmod isn’t given");
+            entry.set_action(Action::Skipped {
+                header_present: false,
+            });
+            return Ok(());
+        }
+        result => result?,
+    };
+    if let Some(before) = before {
+        let outcome = Outcome {
+            path: relative_path(&path, &project)?
+                .to_string_lossy()
+                .into_owned(),
+            action,
+            before,
+            after: read()?,
         };
+        if !args.no_color && io::stdout().is_terminal() {
+            print!("{}", colorize(&outcome.diff()));
+        } else {
+            print!("{}", outcome.diff());
+        }
     }
-    None
+    entry.set_action(action);
+    log_skipped(args, action, &options, log);
+    explain_action(args, log, &path, action, &options, &policy);
+    Ok(())
 }
 
-/// Fills a template with generated or configured data.
+/// Resolves the project, template and options that apply to a file. Nothing
+/// is returned if the configuration says nothing should be done with it.
 ///
 /// # Arguments
-/// * `template` - Template to fill, resulting from the merge of global and language templates.
-/// * `project` - Information on the project the file belongs to.
+/// * `args` - Command line arguments.
+/// * `config` - Global configuration.
 /// * `path` - Path of the file.
-/// * `root` - Path to the root of the project the file belongs to.
-///
-/// # Example
-/// ```
-/// # let args = Args::parse();
-/// # let config = toml::from_str(fs::read_to_string(args.config)?.as_str()).unwrap();
-/// let project = find_project(&config, &args.path).unwrap().merge(&config.data);
-/// let lang_conf = match get_language_config(&config, &language).unwrap().merge(&config.default);
-/// let header = fill_template(&lang_conf, &project, &args.path, &project.root);
-/// ```
-fn fill_template(template: &Template, project: &Project, path: &str, root: &str) -> Vec<String> {
-    let path = Path::new(&env::current_dir().unwrap()).join(path);
-    let creation_date: DateTime<Local> = fs::metadata(path.clone())
-        .unwrap()
-        .created()
-        .unwrap()
-        .into();
-    let creation_date = creation_date.format("%A %d %B %Y").to_string();
-    let modification_date: DateTime<Local> = fs::metadata(path.clone())
-        .unwrap()
-        .modified()
-        .unwrap()
-        .into();
-    let modification_date = modification_date
-        .format("%A %d %B %Y @ %H:%M:%S")
-        .to_string();
-    let year = Local::now().format("%Y").to_string();
-    let data = project.data.clone().unwrap();
-
-    let mut res = template
-        .template
-        .clone()
-        .unwrap_or_default()
-        .as_str()
-        .replace(
-            "#copyright_notice",
-            &project
-                .data
-                .as_ref()
-                .unwrap()
-                .copyright_notice
-                .clone()
-                .unwrap(),
-        )
-        .to_string();
-
-    let path = path.strip_prefix(root).unwrap();
-    res = res
-        .replace("#file_creation", &creation_date)
-        .replace("#date_now", &modification_date)
-        .replace("#file_relative_path", path.to_str().unwrap_or(""))
-        .replace(
-            "#project_name",
-            &project.name.clone().unwrap_or(String::from(
-                Path::new(&project.root)
-                    .file_name()
-                    .unwrap()
-                    .to_str()
-                    .unwrap(),
-            )),
-        )
-        .replace("#author_name", &data.author.unwrap_or_default())
-        .replace("#cp_year", &year);
-    if data.author_mail.as_ref().is_some_and(|f| !f.is_empty()) {
-        res = res.replace(
-            "#author_mail",
-            format!("<{}>", &data.author_mail.unwrap()).as_str(),
-        );
+/// * `log` - Log of the messages for the file.
+/// * `entry` - Report entry of the file, completed with its project and language.
+fn prepare(
+    args: &Args,
+    config: &Config,
+    path: &str,
+    log: &mut FileLog,
+    entry: &mut ReportEntry,
+) -> Result<Option<(Project, Template, Options)>, AutoHeaderError> {
+    // Get the project’s configuration and check that we’re doing something with it.
+    let Some(project) = find_project(config, path)? else {
+        log.info(format!(
+            "No configuration found for file {}. Exiting.",
+            path
+        ));
+        let reason = match &config.root_marker {
+            Some(marker) => format!(
+                "no configured project’s root, nor directory holding the root_marker {}, is one of its parents",
+                marker
+            ),
+            None => String::from("no configured project’s root is one of its parents"),
+        };
+        explain_skip(args, log, path, reason);
+        return Ok(None);
+    };
+
+    if config
+        .duplicate_roots()
+        .iter()
+        .any(|root| Path::new(root) == Path::new(&project.root))
+    {
+        log.info(format!(
+            "Several projects have the root {}, only the first one is used.",
+            project.root
+        ));
+    }
+    let locale = project.locale.as_ref().unwrap_or(&config.locale);
+    if !is_supported_locale(locale) {
+        log.info(format!(
+            "Locale {} is not supported, falling back to English.",
+            locale
+        ));
+    }
+    entry.project = Some(project.name.clone().unwrap_or(project.root.clone()));
+    let locals = LocalConfig::find(&project, path)?;
+    let mut project = project.resolve(config).with_locals(&locals);
+    let create = project.create.unwrap_or(config.create);
+    let update = project.update.unwrap_or(config.update);
+    let use_git = project.use_git.unwrap_or(config.use_git);
+    if let Some(data) = project.data.as_mut() {
+        data.tokens
+            .get_or_insert_with(Default::default)
+            .extend(args.defines.iter().cloned());
+    }
+
+    // Get the language for the target file.
+    let language = match &args.language {
+        Some(language) => language_name(config, language),
+        None => get_language(config, path),
+    };
+    entry.language = Some(language.clone());
+    let lang_conf = if let Some(name) = &args.template_name {
+        get_named_template(config, name).ok_or(AutoHeaderError::NoTemplate(name.clone()))?
+    } else if let Some(lang_conf) = get_language_config(config, &language) {
+        lang_conf
     } else {
-        res = res.replace("#author_mail", "");
+        // The files of a walked directory having no template are frequent.
+        if !walking(args) {
+            log.info(format!(
+                "No configuration found for file {} (language {}). Exiting.",
+                path, language
+            ));
+        }
+        explain_skip(
+            args,
+            log,
+            path,
+            format!(
+                "no template applies to its language ({}), and language_strict forbids using the default one",
+                language
+            ),
+        );
+        return Ok(None);
+    };
+    let lang_conf = config.with_includes(
+        lang_conf
+            .merge(&config.default)
+            .with_locals(&locals)
+            .with_project(&project),
+    )?;
+    if let Some(data) = &project.data {
+        data.check(&lang_conf)?;
     }
-    if data.cp_holders.as_ref().is_some_and(|f| !f.is_empty()) {
-        res = res.replace(
-            "#cp_holders",
-            format!("<{}>", &data.cp_holders.unwrap()).as_str(),
+    // The language’s policy takes precedence over the project’s.
+    let create = !(args.no_create || args.update_only) && lang_conf.create.unwrap_or(create);
+    let update = !args.no_update && lang_conf.update.unwrap_or(update);
+    if !create && !update {
+        log.info(
+            "Project’s or language’s configuration forbids creation and update of headers: exiting.",
         );
-    } else {
-        res = res.replace("#cp_holders", "");
+        let policy = policy_origin(args, config, &project, &lang_conf);
+        let reason = format!("creation and update are both disabled: {}", policy);
+        explain_skip(args, log, path, reason);
+        return Ok(None);
     }
-
-    let prefix = template.prefix.clone().unwrap_or_default();
-    template
-        .before
-        .clone()
-        .unwrap_or_default()
-        .into_iter()
-        .chain(res.split('\n').map(|s| format!("{}{}", prefix, s)))
-        .chain(template.after.clone().unwrap_or_default())
-        .collect()
+    // The history is read once, for the dates and the author.
+    let commits = use_git.then(|| file_commits(Path::new(path)));
+    if let (Some(data), Some(first)) = (
+        project.data.as_mut(),
+        commits.as_ref().and_then(|commits| commits.last()),
+    ) {
+        // Without an author in the configuration, the file’s first one is used.
+        let unset = |value: &Option<String>| value.as_deref().is_none_or(str::is_empty);
+        if unset(&data.author) {
+            data.author = Some(first.author.clone());
+        }
+        if unset(&data.author_mail) {
+            data.author_mail = Some(first.mail.clone());
+        }
+    }
+    // Check if it’s an update or creation, and update / adds the header in the file.
+    let options = Options {
+        create,
+        update,
+        force: args.force,
+        chmod: args.chmod,
+        output: args
+            .output
+            .as_deref()
+            .map(|output| expand_path(output).into()),
+        timestamp: args.timestamp_source,
+        dry_run: args.dry_run || args.check,
+        only_changed_lines: args.only_changed_lines,
+        require_birthtime: args.fail_on_missing_birthtime,
+        skip_empty: project.skip_empty_files.unwrap_or(config.skip_empty_files),
+        normalize_prefix: args.normalize_prefix,
+        use_git,
+        commits,
+    };
+    if !options.require_birthtime && Path::new(path).exists() && !has_birthtime(path) {
+        log.info(format!(
+            "Creation date of {} is unavailable, using its modification date.",
+            path
+        ));
+    }
+    Ok(Some((project, lang_conf, options)))
 }
 
-/// Check if a matching header is found in the given file.
+/// Reads content from the standard input, and writes it with its header on
+/// the standard output (unchanged if nothing applies to it). Gets what was
+/// done to the content, if anything applied to it.
 ///
 /// # Arguments
-/// * `path` - Path to the file.
-/// * `header` - Header generated.
-/// * `template` - Template the header was generated from.
-///
-/// # Example
-/// ```
-/// # let args = Args::parse();
-/// # let config = toml::from_str(fs::read_to_string(args.config)?.as_str()).unwrap();
-/// # let project = find_project(&config, &args.path).unwrap().merge(&config.data);
-/// # let lang_conf = match get_language_config(&config, &language).unwrap().merge(&config.default);
-/// let header = fill_template(&lang_conf, &project, &args.path, &project.root);
-/// let exists = check_header_exists(&args.path, &header, &lang_conf);
-/// ```
-fn check_header_exists(path: &str, header: &[String], template: &Template) -> bool {
-    let mut file = File::open(path).unwrap();
-    let mut content = String::new();
-    file.read_to_string(&mut content).unwrap();
-    let content: Vec<String> = content.split('\n').map(|s| s.to_owned()).collect();
-
-    if content.len() < header.len() {
-        return false;
-    }
-    let prefix = template.prefix.clone().unwrap_or_default();
-    let tracked = template.track_changes.clone().unwrap_or_default();
-    for (ci, hi) in content.iter().zip(header.iter()) {
-        if hi.trim_end() != ci.trim_end()
-            && !ci.contains("Creation date")
-            && !tracked
-                .iter()
-                .any(|t| ci.replace(&prefix, "").starts_with(t.as_str()))
-        {
-            return false;
+/// * `args` - Command line arguments.
+/// * `path` - Path the content is processed as.
+/// * `target` - Project, template and options that apply to the content.
+/// * `log` - Log of the messages for the content.
+fn process_stdin(
+    args: &Args,
+    path: &str,
+    target: Option<(Project, Template, Options)>,
+    log: &mut FileLog,
+) -> Result<Option<Action>, AutoHeaderError> {
+    let mut input = Vec::new();
+    io::stdin()
+        .read_to_end(&mut input)
+        .map_err(AutoHeaderError::read(STDIN))?;
+    let mut done = None;
+    let output = match target {
+        Some((project, lang_conf, options)) => {
+            let encoding = template_encoding(&lang_conf)?;
+            let content = decode_lines(STDIN, &input, encoding)?;
+            let dates = options.timestamp.map_or_else(FileDates::now, FileDates::at);
+            let (action, content) =
+                process_content(path, &content, &dates, &project, &lang_conf, &options)?;
+            log_skipped(args, action, &options, log);
+            done = Some(action);
+            encode_lines(STDIN, &content, encoding)?
         }
-    }
-    true
+        None => input,
+    };
+    io::stdout()
+        .write_all(&output)
+        .map_err(AutoHeaderError::write(STDIN))?;
+    Ok(done)
 }
 
-/// Updates the fields specified in the track_changes field of the templates for an
-/// existing header.
+/// Describes the creation and update policy applying to a file, and where each
+/// setting comes from (command line, template, project or global configuration).
 ///
 /// # Arguments
-/// * `path` - Path to the file.
-/// * `header` - New generated header.
-/// * `template` - Template the header was generated with.
-///
-/// # Example
-/// ```
-/// # let args = Args::parse();
-/// # let config = toml::from_str(fs::read_to_string(args.config)?.as_str()).unwrap();
-/// # let project = find_project(&config, &args.path).unwrap().merge(&config.data);
-/// # let lang_conf = match get_language_config(&config, &language).unwrap().merge(&config.default);
-/// let header = fill_template(&lang_conf, &project, &args.path, &project.root);
-/// let _ = update_header(&args.path, &header, &lang_conf);
-///    
-/// ```
-fn update_header(path: &str, header: &[String], template: &Template) -> Result<(), Box<dyn Error>> {
-    let mut f = File::open(path)?;
-    let mut content: Vec<u8> = Vec::new();
-    f.read_to_end(&mut content)?;
-    let content: String = str::from_utf8(&content)?.to_string();
-    let mut content: Vec<String> = content.split('\n').map(|s| s.to_string()).collect();
-    let tracked = template.track_changes.clone().unwrap_or_default();
-    let prefix = template.prefix.clone().unwrap_or_default();
-    header.iter().enumerate().for_each(|(i, h)| {
-        if tracked
-            .iter()
-            .any(|s| h.replace(&prefix, "").starts_with(s.as_str()))
-        {
-            content[i] = h.to_string();
-        }
-    });
-    let mut f = File::create(path)?;
-    f.write_all(content.join("\n").as_bytes())?;
-    Ok(())
+/// * `args` - Command line arguments.
+/// * `config` - Global configuration.
+/// * `project` - Project the file belongs to (resolved).
+/// * `template` - Template of the file (merged).
+fn policy_origin(args: &Args, config: &Config, project: &Project, template: &Template) -> String {
+    let template_name = if template.name.is_empty() {
+        String::from("default template")
+    } else {
+        format!("template {}", template.name.join(", "))
+    };
+    let project_name = format!(
+        "project {}",
+        project.name.clone().unwrap_or(project.root.clone())
+    );
+    let origin = |name: &str, flag: Option<&str>, settings: [Option<bool>; 2], global: bool| {
+        let (value, from) = if let Some(flag) = flag {
+            (false, flag.to_owned())
+        } else if let Some(value) = settings[0] {
+            (value, template_name.clone())
+        } else if let Some(value) = settings[1] {
+            (value, project_name.clone())
+        } else {
+            (global, String::from("global configuration"))
+        };
+        format!("{} = {} ({})", name, value, from)
+    };
+    format!(
+        "{}, {}",
+        origin(
+            "create",
+            if args.no_create {
+                Some("--no-create")
+            } else {
+                args.update_only.then_some("--update-only")
+            },
+            [template.create, project.create],
+            config.create
+        ),
+        origin(
+            "update",
+            args.no_update.then_some("--no-update"),
+            [template.update, project.update],
+            config.update
+        ),
+    )
 }
 
-/// Writes a new header to the file.
+/// Logs why a file was skipped, with `--explain-skip`.
 ///
 /// # Arguments
-/// * `path` - Path to the file.
-/// * `header` - New generated header.
+/// * `args` - Command line arguments.
+/// * `log` - Log of the messages for the file.
+/// * `path` - Path of the file.
+/// * `reason` - Why the file was skipped.
+fn explain_skip(args: &Args, log: &mut FileLog, path: &str, reason: impl Display) {
+    if args.explain_skip {
+        log.info(format!("{} skipped: {}.", path, reason));
+    }
+}
+
+/// Explains why nothing was done to a processed file with `--explain-skip`,
+/// if that’s the case.
 ///
-/// # Example
-/// ```
-/// # let args = Args::parse();
-/// # let config = toml::from_str(fs::read_to_string(args.config)?.as_str()).unwrap();
-/// # let project = find_project(&config, &args.path).unwrap().merge(&config.data);
-/// # let lang_conf = match get_language_config(&config, &language).unwrap().merge(&config.default);
-/// let header = fill_template(&lang_conf, &project, &args.path, &project.root);
-/// let _ = write_header(&args.path, &header, &lang_conf);
-///    
-/// ```
-fn write_header(path: &str, header: &[String]) -> Result<(), Box<dyn Error>> {
-    let header = header.join("\n") + "\n";
-    let mut f = File::open(path)?;
-    let mut content = header.as_bytes().to_owned();
-    f.read_to_end(&mut content)?;
-    let mut f = File::create(path)?;
-    f.write_all(content.as_slice())?;
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `log` - Log of the messages for the file.
+/// * `path` - Path of the file.
+/// * `action` - What was done to the file.
+/// * `options` - Options the file was processed with.
+/// * `policy` - Creation and update policy of the file, with its origin.
+fn explain_action(
+    args: &Args,
+    log: &mut FileLog,
+    path: &str,
+    action: Action,
+    options: &Options,
+    policy: &str,
+) {
+    let Action::Skipped { header_present } = action else {
+        return;
+    };
+    let reason = if header_present {
+        format!(
+            "it already has a header, and updates are disabled: {}",
+            policy
+        )
+    } else if options.create {
+        String::from("it’s empty or blank, and skip_empty_files is set")
+    } else {
+        format!("it has no header, and creation is disabled: {}", policy)
+    };
+    explain_skip(args, log, path, reason);
+}
 
-    Ok(())
+/// Explains why nothing was done to a file, if that’s the case. The files of
+/// a walked directory are only counted as skipped in the run’s summary
+/// (`--explain-skip` still explains them).
+///
+/// # Arguments
+/// * `args` - Command line arguments.
+/// * `action` - What was done to the file.
+/// * `options` - Options the file was processed with.
+/// * `log` - Log of the messages for the file.
+fn log_skipped(args: &Args, action: Action, options: &Options, log: &mut FileLog) {
+    // Frequent when walking a directory (with `--update-only` for example).
+    if walking(args) {
+        return;
+    }
+    if action
+        == (Action::Skipped {
+            header_present: false,
+        })
+        && options.create
+    {
+        log.info("nothing to do: the file is empty, and skip_empty_files is set");
+        return;
+    }
+    if let Action::Skipped { header_present } = action {
+        log.info(format!(
+            "nothing to do: header exists = {} with configuration create = {} and update = {}",
+            header_present, options.create, options.update
+        ));
+    }
 }
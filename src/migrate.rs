@@ -0,0 +1,85 @@
+use toml::{Table, Value};
+
+use crate::error::Result;
+
+/// A transformation upgrading a configuration written for an older version of
+/// the application. It returns a description of every change it made.
+type Migration = fn(&mut Table) -> Vec<String>;
+
+/// Known transformations, applied in order.
+const MIGRATIONS: [Migration; 2] = [move_copyright_notice, rename_bash_language];
+
+/// Upgrades a configuration to the current format.
+///
+/// Comments and formatting of the original file are not kept. If no change
+/// is needed, the list of changes is empty.
+///
+/// # Arguments
+/// * `content` - Content of the configuration file.
+///
+/// # Example
+/// ```
+/// use auto_header::migrate::migrate;
+///
+/// let (migrated, changes) = migrate("[[language]]\nname = \"bash\"\nprefix = \"# \"\n")?;
+/// assert_eq!(changes, ["Renamed language bash to shell."]);
+/// assert!(migrated.contains("name = \"shell\""));
+/// assert!(migrate(&migrated)?.1.is_empty());
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn migrate(content: &str) -> Result<(String, Vec<String>)> {
+    let mut config: Table = toml::from_str(content)?;
+    let changes = MIGRATIONS
+        .iter()
+        .flat_map(|migration| migration(&mut config))
+        .collect();
+    Ok((config.to_string(), changes))
+}
+
+/// The copyright notice is part of the data: when set in the default
+/// template, it’s ignored.
+fn move_copyright_notice(config: &mut Table) -> Vec<String> {
+    let Some(notice) = config
+        .get_mut("default")
+        .and_then(Value::as_table_mut)
+        .and_then(|default| default.remove("copyright_notice"))
+    else {
+        return Vec::new();
+    };
+    let data = config
+        .entry("data")
+        .or_insert_with(|| Value::Table(Table::new()));
+    let Some(data) = data.as_table_mut() else {
+        return Vec::new();
+    };
+    if data.contains_key("copyright_notice") {
+        return vec![String::from(
+            "Removed [default].copyright_notice, overridden by [data].copyright_notice.",
+        )];
+    }
+    data.insert(String::from("copyright_notice"), notice);
+    vec![String::from(
+        "Moved [default].copyright_notice to [data].copyright_notice.",
+    )]
+}
+
+/// Shell scripts are detected as the `shell` language, never as `bash`.
+fn rename_bash_language(config: &mut Table) -> Vec<String> {
+    let Some(languages) = config.get_mut("language").and_then(Value::as_array_mut) else {
+        return Vec::new();
+    };
+    languages
+        .iter_mut()
+        .filter_map(Value::as_table_mut)
+        .filter_map(|language| language.get_mut("name"))
+        .flat_map(|name| match name {
+            Value::Array(names) => names.iter_mut().collect(),
+            name => vec![name],
+        })
+        .filter(|name| name.as_str() == Some("bash"))
+        .map(|name| {
+            *name = Value::String(String::from("shell"));
+            String::from("Renamed language bash to shell.")
+        })
+        .collect()
+}
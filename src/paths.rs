@@ -0,0 +1,181 @@
+use std::{
+    env, fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::error::{AutoHeaderError, Result};
+
+/// Expands a leading `~` and any `$VAR` / `${VAR}` references in a path,
+/// the way a shell would. Unknown variables are left untouched.
+///
+/// # Arguments
+/// * `path` - Path to expand.
+///
+/// # Example
+/// ```
+/// use auto_header::paths::expand_path;
+///
+/// if let Ok(home) = std::env::var("HOME") {
+///     assert_eq!(expand_path("~/.config/auto-header"), format!("{}/.config/auto-header", home));
+///     assert_eq!(expand_path("${HOME}/code/auto-header.rs"), format!("{}/code/auto-header.rs", home));
+/// }
+/// assert_eq!(expand_path("$AUTO_HEADER_UNKNOWN/src"), "$AUTO_HEADER_UNKNOWN/src");
+/// ```
+pub fn expand_path(path: &str) -> String {
+    let path = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => match env::var("HOME") {
+            Ok(home) => format!("{}{}", home, rest),
+            Err(_) => path.to_owned(),
+        },
+        _ => path.to_owned(),
+    };
+
+    let mut res = String::with_capacity(path.len());
+    let mut chars = path.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            res.push(c);
+            continue;
+        }
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                name.push(c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        let closed = braced && chars.peek() == Some(&'}');
+        if closed {
+            chars.next();
+        }
+        match env::var(&name) {
+            Ok(value) if !name.is_empty() && (closed || !braced) => res.push_str(&value),
+            _ => {
+                res.push('$');
+                if braced {
+                    res.push('{');
+                }
+                res.push_str(&name);
+                if closed {
+                    res.push('}');
+                }
+            }
+        }
+    }
+    res
+}
+
+/// Default path of the configuration file, following the XDG convention and
+/// falling back to `~/.config` when `XDG_CONFIG_HOME` isn’t set.
+pub fn default_config_path() -> String {
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => format!("{}/auto-header/configuration.toml", dir),
+        _ => String::from("~/.config/auto-header/configuration.toml"),
+    }
+}
+
+/// Gets the absolute path of a file, a relative path being taken from the
+/// current directory. Fails if the current directory can’t be determined (it
+/// was removed for example).
+///
+/// # Arguments
+/// * `path` - Path to make absolute.
+///
+/// # Example
+/// ```
+/// use auto_header::paths::absolute_path;
+/// use std::path::Path;
+///
+/// let path = absolute_path(Path::new("./src/main.rs"))?;
+/// assert!(path.is_absolute());
+/// assert!(path.ends_with("src/main.rs"));
+/// # Ok::<(), auto_header::error::AutoHeaderError>(())
+/// ```
+pub fn absolute_path(path: &Path) -> Result<PathBuf> {
+    let dir = env::current_dir().map_err(AutoHeaderError::read("."))?;
+    Ok(dir.join(path))
+}
+
+/// Checks whether a path is inside a `.git` directory, whose files must never
+/// be modified (that could corrupt the repository). Symbolic links are
+/// resolved when the path exists.
+///
+/// # Arguments
+/// * `path` - Path to check.
+///
+/// # Example
+/// ```
+/// use auto_header::paths::in_git_dir;
+///
+/// assert!(in_git_dir("project/.git/hooks/pre-commit"));
+/// assert!(!in_git_dir("project/src/main.rs"));
+/// ```
+pub fn in_git_dir(path: &str) -> bool {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| Path::new(path).to_path_buf());
+    path.components()
+        .any(|component| component == Component::Normal(".git".as_ref()))
+}
+
+/// Gets the files of a directory and of its subdirectories, sorted. The `.git`
+/// directories and the symbolic links to directories are never walked. A
+/// directory that can’t be read doesn’t stop the walk: it’s returned with the
+/// error, along with the files found elsewhere.
+///
+/// # Arguments
+/// * `dir` - Directory to walk.
+/// * `max_depth` - Maximum depth of the files, if any: 1 for the files of the
+///   directory only, 2 for those of its subdirectories too, *etc.*
+///
+/// # Example
+/// ```ignore
+/// let (files, errors) = directory_files("src", Some(2));
+/// for path in files {
+///     process_file(&path, &project, &template, &options)?;
+/// }
+/// ```
+pub fn directory_files(
+    dir: &str,
+    max_depth: Option<usize>,
+) -> (Vec<String>, Vec<(String, AutoHeaderError)>) {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    let mut dirs = vec![(Path::new(dir).to_path_buf(), 1)];
+    while let Some((dir, depth)) = dirs.pop() {
+        if max_depth.is_some_and(|max| depth > max) {
+            continue;
+        }
+        let name = dir.to_string_lossy().into_owned();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                errors.push((name.clone(), AutoHeaderError::read(&name)(err)));
+                continue;
+            }
+        };
+        for entry in entries {
+            let (file_type, entry) = match entry.and_then(|e| Ok((e.file_type()?, e))) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    errors.push((name.clone(), AutoHeaderError::read(&name)(err)));
+                    continue;
+                }
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if entry.file_name() != ".git" {
+                    dirs.push((path, depth + 1));
+                }
+            } else if path.is_file() {
+                files.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    files.sort();
+    (files, errors)
+}
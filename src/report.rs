@@ -0,0 +1,303 @@
+use serde::Serialize;
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::Path,
+    process::Stdio,
+};
+
+use crate::{
+    error::{AutoHeaderError, Result},
+    header::shell_command,
+    Action,
+};
+
+/// What happened to a file during a run, as written in the report file.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ReportEntry {
+    /// Path of the file.
+    pub path: String,
+    /// Language the file was processed as.
+    pub language: Option<String>,
+    /// Project the file belongs to (its name, or its root if it has none).
+    pub project: Option<String>,
+    /// What was done to the file.
+    pub action: Option<String>,
+    /// Why the file couldn’t be processed.
+    pub error: Option<String>,
+}
+
+impl ReportEntry {
+    /// Creates the entry of a file, before it’s processed.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Records what was done to the file.
+    ///
+    /// # Arguments
+    /// * `action` - Action performed.
+    pub fn set_action(&mut self, action: Action) {
+        self.action = Some(action.to_string());
+    }
+
+    /// Checks whether the file was (or would be) modified.
+    pub fn changed(&self) -> bool {
+        matches!(
+            self.action.as_deref(),
+            Some("created" | "updated" | "recreated" | "replaced")
+        )
+    }
+}
+
+/// Number of files by outcome of a run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    /// Files whose header was created.
+    pub created: usize,
+    /// Files whose header was updated.
+    pub updated: usize,
+    /// Files whose damaged header was recreated.
+    pub recreated: usize,
+    /// Files whose header written with another comment syntax was replaced.
+    pub replaced: usize,
+    /// Files whose header was already up to date.
+    pub up_to_date: usize,
+    /// Files to which nothing applied.
+    pub skipped: usize,
+    /// Files that couldn’t be processed.
+    pub failed: usize,
+}
+
+impl RunSummary {
+    /// Counts the files of a run by outcome.
+    ///
+    /// # Arguments
+    /// * `entries` - Entries of the processed files.
+    pub fn new(entries: &[ReportEntry]) -> Self {
+        let mut summary = Self::default();
+        for entry in entries {
+            let count = match (&entry.error, entry.action.as_deref()) {
+                (Some(_), _) => &mut summary.failed,
+                (None, Some("created")) => &mut summary.created,
+                (None, Some("updated")) => &mut summary.updated,
+                (None, Some("recreated")) => &mut summary.recreated,
+                (None, Some("replaced")) => &mut summary.replaced,
+                (None, Some("up-to-date")) => &mut summary.up_to_date,
+                (None, _) => &mut summary.skipped,
+            };
+            *count += 1;
+        }
+        summary
+    }
+
+    /// Counts by outcome, named as the environment variables given to the
+    /// completion command.
+    fn variables(&self) -> [(&'static str, usize); 7] {
+        [
+            ("AUTO_HEADER_CREATED", self.created),
+            ("AUTO_HEADER_UPDATED", self.updated),
+            ("AUTO_HEADER_RECREATED", self.recreated),
+            ("AUTO_HEADER_REPLACED", self.replaced),
+            ("AUTO_HEADER_UP_TO_DATE", self.up_to_date),
+            ("AUTO_HEADER_SKIPPED", self.skipped),
+            ("AUTO_HEADER_FAILED", self.failed),
+        ]
+    }
+}
+
+/// Runs the completion command of a run (`on_complete`), giving it the summary
+/// of the run as JSON on its standard input and as environment variables.
+///
+/// # Arguments
+/// * `command` - Shell command to run.
+/// * `entries` - Entries of the processed files.
+///
+/// # Example
+/// ```ignore
+/// if let Some(command) = &config.on_complete {
+///     on_complete(command, &entries)?;
+/// }
+/// ```
+pub fn on_complete(command: &str, entries: &[ReportEntry]) -> Result<()> {
+    let failure = |reason: String| AutoHeaderError::OnComplete {
+        command: command.to_owned(),
+        reason,
+    };
+    let summary = RunSummary::new(entries);
+    let mut child = shell_command(command)
+        .envs(
+            summary
+                .variables()
+                .map(|(name, count)| (name, count.to_string())),
+        )
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| failure(err.to_string()))?;
+    let input = serde_json::to_string(&summary).expect("summary is serializable") + "\n";
+    let written = child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes());
+    // A command not reading its input is reported through its exit status.
+    match written {
+        Err(err) if err.kind() != ErrorKind::BrokenPipe => return Err(failure(err.to_string())),
+        _ => (),
+    }
+    let status = child.wait().map_err(|err| failure(err.to_string()))?;
+    if !status.success() {
+        return Err(failure(status.to_string()));
+    }
+    Ok(())
+}
+
+/// Writes the report of a run, as JSON or CSV depending on the extension of
+/// the report file.
+///
+/// # Arguments
+/// * `path` - Path of the report file (`.json` or `.csv`).
+/// * `entries` - Entries of the processed files.
+///
+/// # Example
+/// ```ignore
+/// let mut entry = ReportEntry::new("./src/main.rs");
+/// entry.set_action(process_file("./src/main.rs", &project, &template, &options)?);
+/// write_report("report.json", &[entry])?;
+/// ```
+pub fn write_report(path: &str, entries: &[ReportEntry]) -> Result<()> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let content = match extension.as_str() {
+        "json" => serde_json::to_string_pretty(entries).expect("entries are serializable") + "\n",
+        "csv" => to_csv(entries),
+        _ => return Err(AutoHeaderError::ReportFormat(path.to_owned())),
+    };
+    fs::write(path, content).map_err(AutoHeaderError::write(path))
+}
+
+/// Formats the entries as CSV, with a header row.
+fn to_csv(entries: &[ReportEntry]) -> String {
+    let mut csv = String::from("path,language,project,action,error\n");
+    for entry in entries {
+        let fields = [
+            Some(&entry.path),
+            entry.language.as_ref(),
+            entry.project.as_ref(),
+            entry.action.as_ref(),
+            entry.error.as_ref(),
+        ];
+        let row: Vec<String> = fields
+            .iter()
+            .map(|field| csv_field(field.map(String::as_str).unwrap_or_default()))
+            .collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a CSV field if needed.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Sections of a grouped dry-run summary: title, ANSI color code, and actions
+/// of the files it lists.
+const SECTIONS: [(&str, &str, &[&str]); 4] = [
+    ("To create", "32", &["created"]),
+    ("To update", "33", &["updated", "recreated", "replaced"]),
+    ("Up to date", "34", &["up-to-date"]),
+    ("Skipped", "2", &["skipped"]),
+];
+
+/// Formats what a dry run would do to the files, one line per file or grouped
+/// by action (files that couldn’t be processed coming last).
+///
+/// # Arguments
+/// * `entries` - Entries of the processed files.
+/// * `group` - Whether the files are grouped in sections by action.
+/// * `color` - Whether the section titles are colored with ANSI codes.
+///
+/// # Example
+/// ```
+/// use auto_header::{report::{dry_run_summary, ReportEntry}, Action};
+///
+/// let mut main = ReportEntry::new("src/main.rs");
+/// main.set_action(Action::Created);
+/// let mut lib = ReportEntry::new("src/lib.rs");
+/// lib.set_action(Action::UpToDate);
+/// let entries = [main, lib];
+/// assert_eq!(
+///     dry_run_summary(&entries, false, false),
+///     "src/main.rs: created\nsrc/lib.rs: up-to-date\n"
+/// );
+/// assert_eq!(
+///     dry_run_summary(&entries, true, false),
+///     "To create (1):\n  src/main.rs\nUp to date (1):\n  src/lib.rs\n"
+/// );
+/// ```
+pub fn dry_run_summary(entries: &[ReportEntry], group: bool, color: bool) -> String {
+    let paint = |code: &str, text: &str| {
+        if color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_owned()
+        }
+    };
+    if !group {
+        return entries
+            .iter()
+            .map(|entry| match (&entry.error, &entry.action) {
+                (Some(error), _) => format!("{}: failed ({})\n", entry.path, error),
+                (None, Some(action)) => format!("{}: {}\n", entry.path, action),
+                (None, None) => format!("{}: skipped\n", entry.path),
+            })
+            .collect();
+    }
+
+    let mut summary = String::new();
+    let mut section = |title: &str, code: &str, paths: Vec<&str>| {
+        if paths.is_empty() {
+            return;
+        }
+        summary.push_str(&paint(code, &format!("{} ({}):", title, paths.len())));
+        summary.push('\n');
+        for path in paths {
+            summary.push_str(&format!("  {}\n", path));
+        }
+    };
+    for (title, code, actions) in SECTIONS {
+        let paths = entries
+            .iter()
+            .filter(|entry| entry.error.is_none())
+            .filter(|entry| match &entry.action {
+                Some(action) => actions.contains(&action.as_str()),
+                // Nothing applied to the file.
+                None => actions.contains(&"skipped"),
+            })
+            .map(|entry| entry.path.as_str())
+            .collect();
+        section(title, code, paths);
+    }
+    let failed = entries
+        .iter()
+        .filter(|entry| entry.error.is_some())
+        .map(|entry| entry.path.as_str())
+        .collect();
+    section("Failed", "31", failed);
+    summary
+}
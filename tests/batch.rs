@@ -0,0 +1,80 @@
+mod common;
+
+use common::{Sandbox, CONFIG};
+use std::{
+    fs,
+    process::{Command, Output},
+};
+
+/// Creates a sandbox holding valid files and one that can’t be decoded, along
+/// with its configuration file. Gets the path of the configuration.
+fn sandbox() -> (Sandbox, String) {
+    let sandbox = Sandbox::new();
+    let config = sandbox.file(
+        "auto-header.toml",
+        &format!("{}\n[[project]]\nroot = {:?}\n", CONFIG, sandbox.root),
+    );
+    sandbox.file("a.rs", "fn a() {}\n");
+    fs::write(sandbox.root.join("b.rs"), b"// \xff\xfe\n").unwrap();
+    sandbox.file("c.rs", "fn c() {}\n");
+    (sandbox, config)
+}
+
+/// Runs auto-header on the sandbox’s directory.
+fn run(sandbox: &Sandbox, config: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_auto-header"))
+        .args(["--config", config, "--path"])
+        .arg(&sandbox.root)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn failing_file_does_not_stop_the_others() {
+    let (sandbox, config) = sandbox();
+
+    let output = run(&sandbox, &config);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let errors = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        errors.contains("1 of 3 file(s) couldn’t be processed"),
+        "{}",
+        errors
+    );
+    assert!(errors.contains("b.rs"), "{}", errors);
+    let messages = String::from_utf8_lossy(&output.stdout);
+    assert!(messages.contains("2 header(s) created"), "{}", messages);
+    assert!(messages.contains("1 failed"), "{}", messages);
+    for name in ["a.rs", "c.rs"] {
+        let path = sandbox.root.join(name).to_string_lossy().into_owned();
+        assert!(sandbox
+            .read(&path)
+            .starts_with(&format!("// File: {}\n", name)));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_file_does_not_stop_the_others() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (sandbox, config) = sandbox();
+    let locked = sandbox.file("d.rs", "fn d() {}\n");
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    // Permissions don’t apply to the superuser.
+    if fs::read(&locked).is_ok() {
+        return;
+    }
+
+    let output = run(&sandbox, &config);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let errors = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        errors.contains("2 of 4 file(s) couldn’t be processed"),
+        "{}",
+        errors
+    );
+    assert!(errors.contains("d.rs"), "{}", errors);
+    let path = sandbox.root.join("c.rs").to_string_lossy().into_owned();
+    assert!(sandbox.read(&path).starts_with("// File: c.rs\n"));
+}
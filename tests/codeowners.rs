@@ -0,0 +1,94 @@
+mod common;
+
+use auto_header::{codeowners::file_owners, Action};
+use common::{Sandbox, CONFIG};
+use std::fs;
+
+/// Creates a sandbox holding a `CODEOWNERS` file.
+///
+/// # Arguments
+/// * `location` - Location of the `CODEOWNERS` file, relative to the root.
+/// * `rules` - Content of the `CODEOWNERS` file.
+fn sandbox(location: &str, rules: &str) -> Sandbox {
+    let sandbox = Sandbox::with_config(
+        &CONFIG.replace("[data]\n", "[data]\nauthor_from_codeowners = true\n"),
+    );
+    let path = sandbox.root.join(location);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, rules).unwrap();
+    sandbox
+}
+
+/// Gets the owners of a file of a sandbox.
+fn owners(sandbox: &Sandbox, path: &str) -> Option<Vec<String>> {
+    file_owners(&sandbox.root.to_string_lossy(), path)
+}
+
+#[test]
+fn last_matching_rule_wins() {
+    let sandbox = sandbox(
+        "CODEOWNERS",
+        "* @everyone\n/src/ @src-team\n*.rs @rustaceans @jane # Rust files\n",
+    );
+
+    assert_eq!(
+        owners(&sandbox, "src/main.rs"),
+        Some(vec![String::from("@rustaceans"), String::from("@jane")])
+    );
+    assert_eq!(
+        owners(&sandbox, "src/main.c"),
+        Some(vec![String::from("@src-team")])
+    );
+    assert_eq!(
+        owners(&sandbox, "README.md"),
+        Some(vec![String::from("@everyone")])
+    );
+}
+
+#[test]
+fn anchored_patterns_match_from_the_root() {
+    let sandbox = sandbox(
+        ".github/CODEOWNERS",
+        "/build/ @builders\ndocs/*.md @writers\n",
+    );
+
+    assert!(owners(&sandbox, "build/main.rs").is_some());
+    assert!(owners(&sandbox, "src/build/main.rs").is_none());
+    assert!(owners(&sandbox, "docs/index.md").is_some());
+    assert!(owners(&sandbox, "docs/api/index.md").is_none());
+    assert!(owners(&sandbox, "src/docs/index.md").is_none());
+}
+
+#[test]
+fn unanchored_patterns_match_anywhere() {
+    let sandbox = sandbox(".gitlab/CODEOWNERS", "build/ @builders\n*.md @writers\n");
+
+    assert!(owners(&sandbox, "build/main.rs").is_some());
+    assert!(owners(&sandbox, "src/build/main.rs").is_some());
+    assert!(owners(&sandbox, "docs/api/index.md").is_some());
+    assert!(owners(&sandbox, "src/main.rs").is_none());
+}
+
+#[test]
+fn files_without_owners_fall_back_to_the_author() {
+    let sandbox = sandbox("CODEOWNERS", "*.c @c-team\n/generated/\n");
+    assert_eq!(owners(&sandbox, "src/main.rs"), None);
+    // A rule without owners unsets those of the previous rules.
+    assert_eq!(owners(&sandbox, "generated/main.c"), None);
+
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    assert!(sandbox
+        .read(&path)
+        .contains("// Author: Jane Doe <jane@doe.org>\n"));
+
+    let path = sandbox.file("main.c", "int main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    assert!(sandbox.read(&path).contains(" * Author: @c-team"));
+}
+
+#[test]
+fn projects_without_codeowners_have_no_owners() {
+    let sandbox = Sandbox::new();
+    assert_eq!(owners(&sandbox, "src/main.rs"), None);
+}
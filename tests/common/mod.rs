@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+
+use auto_header::{config::Config, process_file, resolve, Action, Options};
+use std::{
+    env, fs,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Counter used to give each sandbox a unique directory.
+static SANDBOXES: AtomicUsize = AtomicUsize::new(0);
+
+/// Configuration shared by the tests, the project root being filled in by the sandbox.
+pub const CONFIG: &str = r##"
+create = true
+update = true
+language_strict = true
+
+[data]
+author = "Jane Doe"
+author_mail = "jane@doe.org"
+cp_holders = "Jane Doe"
+copyright_notice = "Copyright © #cp_year #cp_holders - All rights reserved"
+
+[default]
+name = "*"
+prefix = "# "
+before = []
+after = [""]
+template = """
+File: #file_relative_path
+Project: #project_name
+Creation date: #file_creation
+Author: #author_name #author_mail
+-----
+Last modified: #date_now
+Modified by: #author_name
+-----
+#copyright_notice"""
+track_changes = ["File: ", "Last modified: ", "Modified by: ", "Copyright "]
+
+[[language]]
+name = "shell"
+before = ["#!/usr/bin/env bash", ""]
+
+[[language]]
+name = "python"
+before = ["#!/usr/bin/env python3", ""]
+
+[[language]]
+name = "lua"
+prefix = "-- "
+
+[[language]]
+name = "rust"
+prefix = "// "
+
+[[language]]
+name = "c"
+prefix = " * "
+block_start = "/*"
+block_end = " */"
+"##;
+
+/// Temporary project directory, removed when dropped.
+pub struct Sandbox {
+    /// Root of the project.
+    pub root: PathBuf,
+    /// Configuration, whose only project is the sandbox.
+    pub config: Config,
+}
+
+impl Sandbox {
+    /// Creates a sandbox using the shared configuration.
+    pub fn new() -> Self {
+        Self::with_config(CONFIG)
+    }
+
+    /// Creates a sandbox with a specific configuration. A `[[project]]` entry
+    /// pointing to the sandbox is appended to it.
+    pub fn with_config(config: &str) -> Self {
+        let root = env::temp_dir().join(format!(
+            "auto-header-{}-{}",
+            process::id(),
+            SANDBOXES.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&root).unwrap();
+        let config = format!("{}\n[[project]]\nroot = {:?}\n", config, root);
+        let config = Config::parse(&config).unwrap();
+        Self { root, config }
+    }
+
+    /// Creates a file in the sandbox, returning its path.
+    pub fn file(&self, name: &str, content: &str) -> String {
+        let path = self.root.join(name);
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    /// Runs the header creation / update on a file.
+    pub fn process(&self, path: &str) -> Action {
+        let (project, template) = resolve(&self.config, path).unwrap();
+        let options = Options {
+            create: true,
+            update: true,
+            ..Default::default()
+        };
+        process_file(path, &project, &template, &options).unwrap()
+    }
+
+    /// Reads a file of the sandbox.
+    pub fn read(&self, path: &str) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
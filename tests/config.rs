@@ -0,0 +1,128 @@
+mod common;
+
+use auto_header::{config::Config, error::AutoHeaderError, Action};
+use common::{Sandbox, CONFIG};
+use std::fs;
+
+#[test]
+fn missing_copyright_notice_is_reported() {
+    let config = CONFIG.replace(
+        "copyright_notice = \"Copyright © #cp_year #cp_holders - All rights reserved\"\n",
+        "",
+    );
+    let err = Config::parse(&config).unwrap_err();
+    assert!(
+        matches!(&err, AutoHeaderError::MissingField(field) if field == "data.copyright_notice"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn copyright_notice_may_be_set_by_every_project() {
+    let config = CONFIG.replace(
+        "copyright_notice = \"Copyright © #cp_year #cp_holders - All rights reserved\"\n",
+        "",
+    ) + "\n[[project]]\nroot = \"/tmp\"\ncopyright_notice = \"#MIT\"\n";
+    assert!(Config::parse(&config).is_ok());
+}
+
+/// Gets the shared configuration with templates including each other.
+///
+/// # Arguments
+/// * `templates` - `[[language]]` entries added to the configuration.
+fn with_templates(templates: &str) -> String {
+    format!(
+        "{}\n{}",
+        CONFIG.replace("[default]\n", "[default]\ninclude = [\"license\"]\n"),
+        templates
+    )
+}
+
+#[test]
+fn include_cycle_is_reported() {
+    let config = with_templates(
+        "[[language]]\nname = \"license\"\ntemplate = \"MIT\"\ninclude = [\"notice\"]\n\n\
+         [[language]]\nname = \"notice\"\ntemplate = \"See LICENSE\"\ninclude = [\"license\"]\n",
+    );
+    let err = Config::load_inline(&config).unwrap_err();
+    assert!(
+        matches!(&err, AutoHeaderError::IncludeCycle(chain) if chain.contains("license -> notice -> license")),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn template_including_itself_is_reported() {
+    let config = with_templates(
+        "[[language]]\nname = \"license\"\ntemplate = \"MIT\"\ninclude = [\"license\"]\n",
+    );
+    assert!(matches!(
+        Config::load_inline(&config),
+        Err(AutoHeaderError::IncludeCycle(_))
+    ));
+}
+
+#[test]
+fn unknown_included_template_is_reported() {
+    let config = CONFIG.replace("[default]\n", "[default]\ninclude = [\"license\"]\n");
+    assert!(matches!(
+        Config::load_inline(&config),
+        Err(AutoHeaderError::NoTemplate(name)) if name == "license"
+    ));
+}
+
+#[test]
+fn includes_are_resolved_in_the_loaded_configuration() {
+    let sandbox = Sandbox::new();
+    fs::create_dir_all(sandbox.root.join("conf")).unwrap();
+    let path = sandbox.root.join("conf").join("auto-header.toml");
+    fs::write(
+        &path,
+        with_templates(
+            "[[language]]\nname = \"license\"\ntemplate = \"MIT\"\ninclude = [\"notice\"]\n\n\
+             [[language]]\nname = \"notice\"\ntemplate = \"See LICENSE\"\n",
+        ),
+    )
+    .unwrap();
+
+    let config = Config::load(Some(&path), &sandbox.root).unwrap();
+    let template = config
+        .with_includes(config.default.clone().merge(&config.default))
+        .unwrap();
+    assert!(template
+        .template
+        .unwrap()
+        .ends_with("#copyright_notice\nMIT\nSee LICENSE"));
+}
+
+#[test]
+fn configured_values_holding_tokens_are_filled() {
+    let config = CONFIG.replace("Modified by: #author_name", "Modified by: #team")
+        + "\n[data.tokens]\nteam = \"#author_name’s team\"\n";
+    let sandbox = Sandbox::with_config(&config);
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    assert!(sandbox
+        .read(&path)
+        .contains("// Modified by: Jane Doe’s team\n"));
+}
+
+#[test]
+fn file_values_holding_tokens_are_not_substituted_again() {
+    let sandbox = Sandbox::with_config(
+        &CONFIG.replace("author = \"Jane Doe\"", "author = \"Jane #cp_holders\""),
+    );
+    let path = sandbox.file("#cp_year.rs", "fn main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+
+    let content = sandbox.read(&path);
+    assert!(content.starts_with("// File: #cp_year.rs\n"), "{}", content);
+    assert!(
+        content.contains("// Author: Jane #cp_holders <jane@doe.org>\n"),
+        "{}",
+        content
+    );
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+}
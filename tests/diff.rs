@@ -0,0 +1,30 @@
+use auto_header::diff::unified_diff;
+
+/// Splits a content into lines, as read from a file.
+fn lines(content: &str) -> Vec<String> {
+    content.split('\n').map(str::to_owned).collect()
+}
+
+#[test]
+fn removed_lines_come_before_added_ones() {
+    let old = lines("// File: old.rs\n// Last modified: 2001\n\nfn main() {}\n");
+    let new = lines("// File: new.rs\n// Last modified: 2002\n\nfn main() {}\n");
+
+    let diff = unified_diff("new.rs", &old, &new);
+    let changes: Vec<&str> = diff
+        .lines()
+        .skip(3)
+        .filter(|line| line.starts_with(['-', '+']))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            "-// File: old.rs",
+            "-// Last modified: 2001",
+            "+// File: new.rs",
+            "+// Last modified: 2002",
+        ],
+        "{}",
+        diff
+    );
+}
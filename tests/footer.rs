@@ -0,0 +1,109 @@
+mod common;
+
+use auto_header::Action;
+use common::{Sandbox, CONFIG};
+
+/// Creates a sandbox whose headers are written at the bottom of the files.
+fn footer_sandbox() -> Sandbox {
+    Sandbox::with_config(&CONFIG.replace(
+        "before = []\nafter = [\"\"]",
+        "position = \"bottom\"\nbefore = [\"\"]\nafter = []",
+    ))
+}
+
+/// Creates a footer on a file, then checks that the body is kept in front of
+/// it, and that running again changes nothing.
+fn assert_footer_idempotent(name: &str, content: &str) -> String {
+    let sandbox = footer_sandbox();
+    let path = sandbox.file(name, content);
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let first = sandbox.read(&path);
+    assert!(
+        first.starts_with(content.trim_end_matches('\n')),
+        "body was modified:\n{}",
+        first
+    );
+    assert!(
+        first.ends_with("<Jane Doe> - All rights reserved\n"),
+        "{:?}",
+        first
+    );
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), first);
+    first
+}
+
+#[test]
+fn footer_is_idempotent() {
+    let result = assert_footer_idempotent("main.rs", "fn main() {}\n");
+    assert!(result.starts_with("fn main() {}\n\n// File: main.rs\n"));
+}
+
+#[test]
+fn footer_without_trailing_newline_is_idempotent() {
+    let result = assert_footer_idempotent("lib.rs", "pub fn foo() {}");
+    assert!(result.starts_with("pub fn foo() {}\n\n// File: lib.rs\n"));
+}
+
+#[test]
+fn footer_on_empty_file_is_idempotent() {
+    assert_footer_idempotent("empty.lua", "");
+}
+
+#[test]
+fn footer_is_not_duplicated_by_updates() {
+    let sandbox = footer_sandbox();
+    let path = sandbox.file("lib.rs", "pub fn foo() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let edited =
+        sandbox
+            .read(&path)
+            .replacen("pub fn foo() {}\n", "pub fn foo() {}\npub fn bar() {}\n", 1);
+    std::fs::write(&path, &edited).unwrap();
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    let updated = sandbox.read(&path);
+    assert!(updated.starts_with("pub fn foo() {}\npub fn bar() {}\n\n// File: lib.rs\n"));
+    assert_eq!(updated.matches("// File: ").count(), 1);
+    assert_ne!(updated, edited);
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), updated);
+}
+
+#[test]
+fn header_at_the_top_is_not_taken_for_a_footer() {
+    let sandbox = footer_sandbox();
+    let path = sandbox.file("main.rs", "// File: main.rs\nfn main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(result.starts_with("// File: main.rs\nfn main() {}\n\n// File: main.rs\n"));
+}
+
+#[test]
+fn refreshed_after_lines_are_updated() {
+    let sandbox = Sandbox::with_config(&CONFIG.replace(
+        "before = []\nafter = [\"\"]",
+        "position = \"bottom\"\nbefore = [\"\"]\nafter = [\"// Built: #file_modified_iso\"]\nrefresh_after = true",
+    ));
+    let path = sandbox.file("lib.rs", "pub fn foo() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let created = sandbox.read(&path);
+    assert!(!created.contains("#file_modified_iso"), "{}", created);
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let edited = format!("pub fn bar() {{}}\n{}", created);
+    std::fs::write(&path, &edited).unwrap();
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    let updated = sandbox.read(&path);
+    let built = |content: &str| content.lines().last().unwrap().to_owned();
+    assert!(built(&updated).starts_with("// Built: "));
+    assert_ne!(built(&updated), built(&created));
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), updated);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use auto_header::{git::file_commits, process_file, resolve, Action, Options};
+use common::Sandbox;
+use std::{path::Path, process::Command};
+
+/// Runs a git command in the sandbox, its commits dated from 2001.
+fn git(sandbox: &Sandbox, args: &[&str]) {
+    let status = Command::new("git")
+        .current_dir(&sandbox.root)
+        .args([
+            "-c",
+            "user.name=John Smith",
+            "-c",
+            "user.email=john@smith.org",
+        ])
+        .args(args)
+        .env("GIT_AUTHOR_DATE", "2001-02-03T04:05:06+00:00")
+        .env("GIT_COMMITTER_DATE", "2001-02-03T04:05:06+00:00")
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?}", args);
+}
+
+/// Processes a file with its dates taken from its git history.
+fn process(sandbox: &Sandbox, path: &str) -> Action {
+    let (project, template) = resolve(&sandbox.config, path).unwrap();
+    let options = Options {
+        create: true,
+        update: true,
+        use_git: true,
+        ..Default::default()
+    };
+    process_file(path, &project, &template, &options).unwrap()
+}
+
+#[test]
+fn creation_date_comes_from_the_first_commit() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    git(&sandbox, &["init", "-q"]);
+    git(&sandbox, &["add", "main.rs"]);
+    git(&sandbox, &["commit", "-q", "-m", "init"]);
+
+    assert_eq!(process(&sandbox, &path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(result.contains("Creation date: "), "{}", result);
+    assert!(result.contains("2001"), "{}", result);
+}
+
+#[test]
+fn untracked_files_keep_their_file_system_dates() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    git(&sandbox, &["init", "-q"]);
+
+    assert_eq!(process(&sandbox, &path), Action::Created);
+    assert!(!sandbox.read(&path).contains("2001"));
+}
+
+#[test]
+fn commits_give_the_authors_of_the_file() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    git(&sandbox, &["init", "-q"]);
+    git(&sandbox, &["add", "main.rs"]);
+    git(&sandbox, &["commit", "-q", "-m", "init"]);
+
+    let commits = file_commits(Path::new(&path));
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].author, "John Smith");
+    assert_eq!(commits[0].mail, "john@smith.org");
+}
+
+#[test]
+fn fetched_commits_give_the_dates() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    git(&sandbox, &["init", "-q"]);
+    git(&sandbox, &["add", "main.rs"]);
+    git(&sandbox, &["commit", "-q", "-m", "init"]);
+
+    let (project, template) = resolve(&sandbox.config, &path).unwrap();
+    let options = Options {
+        create: true,
+        update: true,
+        use_git: true,
+        commits: Some(file_commits(Path::new(&path))),
+        ..Default::default()
+    };
+    assert_eq!(
+        process_file(&path, &project, &template, &options).unwrap(),
+        Action::Created
+    );
+    assert!(sandbox.read(&path).contains("2001"));
+}
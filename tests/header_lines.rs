@@ -0,0 +1,59 @@
+mod common;
+
+use auto_header::{check_file, process_file, resolve, Action, Options};
+use common::{Sandbox, CONFIG};
+use std::{fs, io::Write};
+
+/// Creates a sandbox whose templates give the line count of their header.
+fn sandbox() -> Sandbox {
+    Sandbox::with_config(&CONFIG.replace(
+        "before = []\nafter = [\"\"]",
+        "before = []\nafter = [\"\"]\nheader_lines = 10",
+    ))
+}
+
+/// Creates a file with an up to date header, followed by a body that can’t be
+/// decoded.
+fn headed_file(sandbox: &Sandbox) -> String {
+    let path = sandbox.file("main.rs", "fn main() {\n    run();\n}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(b"// \xff\xfe\n").unwrap();
+    path
+}
+
+#[test]
+fn dry_run_only_reads_the_header() {
+    let sandbox = sandbox();
+    let path = headed_file(&sandbox);
+    let (project, template) = resolve(&sandbox.config, &path).unwrap();
+    let options = Options {
+        create: true,
+        update: true,
+        dry_run: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        process_file(&path, &project, &template, &options).unwrap(),
+        Action::UpToDate
+    );
+    assert_eq!(
+        check_file(&path, &project, &template, &options, None).unwrap(),
+        Action::UpToDate
+    );
+}
+
+#[test]
+fn writing_reads_the_whole_file() {
+    let sandbox = sandbox();
+    let path = headed_file(&sandbox);
+    let (project, template) = resolve(&sandbox.config, &path).unwrap();
+    let options = Options {
+        create: true,
+        update: true,
+        ..Default::default()
+    };
+
+    assert!(process_file(&path, &project, &template, &options).is_err());
+}
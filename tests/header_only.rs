@@ -0,0 +1,68 @@
+mod common;
+
+use auto_header::Action;
+use common::Sandbox;
+
+/// Creates a header in an empty file, and gets the file’s path along with the
+/// header alone (without the blank lines following it).
+fn header_only(sandbox: &Sandbox, name: &str) -> (String, String) {
+    let path = sandbox.file(name, "");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let header = sandbox.read(&path).trim_end_matches('\n').to_owned();
+    (path, header)
+}
+
+/// Writes a file holding exactly a header, then checks it’s left as is.
+fn assert_header_only_up_to_date(name: &str, newline: bool) {
+    let sandbox = Sandbox::new();
+    let (path, header) = header_only(&sandbox, name);
+    let content = if newline { header + "\n" } else { header };
+    std::fs::write(&path, &content).unwrap();
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), content);
+}
+
+/// Writes a file holding exactly an outdated header, then checks it’s updated
+/// without being extended, and that running again changes nothing.
+fn assert_header_only_updated(name: &str, newline: bool) {
+    let sandbox = Sandbox::new();
+    let (path, header) = header_only(&sandbox, name);
+    let outdated = header.replace(&format!("File: {}", name), "File: old.rs");
+    let outdated = if newline { outdated + "\n" } else { outdated };
+    std::fs::write(&path, &outdated).unwrap();
+
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    let updated = sandbox.read(&path);
+    assert_eq!(updated.lines().count(), outdated.lines().count());
+    assert_eq!(updated.ends_with('\n'), newline);
+    assert!(updated.contains(&format!("File: {}", name)), "{}", updated);
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), updated);
+}
+
+#[test]
+fn header_only_is_up_to_date() {
+    assert_header_only_up_to_date("main.rs", true);
+}
+
+#[test]
+fn header_only_without_trailing_newline_is_up_to_date() {
+    assert_header_only_up_to_date("main.rs", false);
+}
+
+#[test]
+fn header_only_with_before_lines_is_up_to_date() {
+    assert_header_only_up_to_date("script.py", false);
+}
+
+#[test]
+fn header_only_is_updated() {
+    assert_header_only_updated("lib.rs", true);
+}
+
+#[test]
+fn header_only_without_trailing_newline_is_updated() {
+    assert_header_only_updated("lib.rs", false);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use auto_header::Action;
+use common::Sandbox;
+
+/// Creates a header on a file, then checks that running again changes nothing.
+fn assert_idempotent(name: &str, content: &str) {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file(name, content);
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let first = sandbox.read(&path);
+    assert!(first.ends_with(content), "body was modified:\n{}", first);
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), first);
+}
+
+#[test]
+fn rust_is_idempotent() {
+    assert_idempotent("main.rs", "fn main() {}\n");
+}
+
+#[test]
+fn python_with_before_lines_is_idempotent() {
+    assert_idempotent("script.py", "print('hello')\n");
+}
+
+#[test]
+fn bash_with_before_lines_is_idempotent() {
+    assert_idempotent("script.sh", "echo hello\n");
+}
+
+#[test]
+fn lua_is_idempotent() {
+    assert_idempotent("init.lua", "return {}\n");
+}
+
+#[test]
+fn block_comment_is_idempotent() {
+    assert_idempotent("main.c", "int main() { return 0; }\n");
+}
+
+#[test]
+fn empty_file_is_idempotent() {
+    assert_idempotent("empty.rs", "");
+}
+
+#[test]
+fn update_after_edit_is_idempotent() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("lib.rs", "pub fn foo() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let edited = sandbox.read(&path) + "pub fn bar() {}\n";
+    std::fs::write(&path, &edited).unwrap();
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    let updated = sandbox.read(&path);
+    assert_ne!(updated, edited);
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), updated);
+}
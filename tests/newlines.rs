@@ -0,0 +1,134 @@
+mod common;
+
+use auto_header::{header::write_header, Action};
+use common::{Sandbox, CONFIG};
+
+/// Creates a header on a file, then checks that the body follows it after a
+/// single separator, unchanged, and that running again changes nothing.
+fn assert_body_kept(sandbox: &Sandbox, name: &str, content: &str) -> String {
+    let path = sandbox.file(name, content);
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let first = sandbox.read(&path);
+    assert!(
+        first.ends_with(&format!("<Jane Doe> - All rights reserved\n\n{}", content)),
+        "body badly separated from the header:\n{:?}",
+        first
+    );
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), first);
+    first
+}
+
+#[test]
+fn missing_trailing_newline_is_kept() {
+    let sandbox = Sandbox::new();
+    let result = assert_body_kept(&sandbox, "main.rs", "fn main() {}");
+    assert!(result.ends_with("fn main() {}"));
+}
+
+#[test]
+fn multiline_body_without_trailing_newline_is_kept() {
+    let sandbox = Sandbox::new();
+    assert_body_kept(&sandbox, "lib.rs", "mod a;\n\nfn main() {}");
+}
+
+#[test]
+fn several_trailing_newlines_are_kept() {
+    let sandbox = Sandbox::new();
+    let result = assert_body_kept(&sandbox, "main.rs", "fn main() {}\n\n\n");
+    assert!(result.ends_with("fn main() {}\n\n\n"));
+}
+
+#[test]
+fn crlf_body_is_kept() {
+    let sandbox = Sandbox::new();
+    let result = assert_body_kept(&sandbox, "main.rs", "fn main() {}\r\n");
+    assert!(result.ends_with("fn main() {}\r\n"));
+}
+
+#[test]
+fn before_lines_without_trailing_newline_are_kept() {
+    let sandbox = Sandbox::new();
+    assert_body_kept(&sandbox, "script.py", "print('hello')");
+}
+
+#[test]
+fn header_without_after_lines_has_a_single_separator() {
+    let sandbox = Sandbox::with_config(&CONFIG.replace("after = [\"\"]", "after = []"));
+    let path = sandbox.file("main.rs", "fn main() {}");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(
+        result.ends_with("<Jane Doe> - All rights reserved\nfn main() {}"),
+        "{:?}",
+        result
+    );
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+}
+
+#[test]
+fn update_without_trailing_newline_is_kept() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("lib.rs", "pub fn foo() {}");
+    assert_eq!(sandbox.process(&path), Action::Created);
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let edited = sandbox.read(&path) + "\npub fn bar() {}";
+    std::fs::write(&path, &edited).unwrap();
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    let updated = sandbox.read(&path);
+    assert!(updated.ends_with("pub fn foo() {}\npub fn bar() {}"));
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), updated);
+}
+
+#[test]
+fn write_header_keeps_the_trailing_state() {
+    let sandbox = Sandbox::new();
+    let header = [String::from("// header"), String::new()];
+
+    let path = sandbox.file("a.rs", "fn main() {}");
+    write_header(&path, &header).unwrap();
+    assert_eq!(sandbox.read(&path), "// header\n\nfn main() {}");
+
+    let path = sandbox.file("b.rs", "fn main() {}\n");
+    write_header(&path, &header).unwrap();
+    assert_eq!(sandbox.read(&path), "// header\n\nfn main() {}\n");
+}
+
+#[test]
+fn write_header_on_empty_file_ends_with_the_header() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("empty.rs", "");
+    write_header(&path, &[String::from("// header")]).unwrap();
+    assert_eq!(sandbox.read(&path), "// header\n");
+}
+
+#[test]
+fn file_holding_only_before_lines_keeps_its_trailing_newline() {
+    let sandbox = Sandbox::with_config(&CONFIG.replace("after = [\"\"]", "after = []"));
+    let path = sandbox.file("script.py", "#!/usr/bin/env python3\n");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(result.starts_with("#!/usr/bin/env python3\n\n# File: "));
+    assert!(
+        result.ends_with("<Jane Doe> - All rights reserved\n"),
+        "{:?}",
+        result
+    );
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+}
+
+#[test]
+fn crlf_template_leaves_no_carriage_return() {
+    // Escaped carriage returns, as the multi-line strings’ line endings are
+    // normalized by the TOML parser.
+    let sandbox = Sandbox::with_config(&CONFIG.replace("#author_mail\n", "#author_mail\\r\n"));
+    let result = assert_body_kept(&sandbox, "main.rs", "fn main() {}\n");
+    assert!(!result.contains('\r'), "{:?}", result);
+}
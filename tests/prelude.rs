@@ -0,0 +1,66 @@
+mod common;
+
+use auto_header::Action;
+use common::{Sandbox, CONFIG};
+
+/// Creates a sandbox whose rust template has some leading lines option.
+fn sandbox(option: &str) -> Sandbox {
+    Sandbox::with_config(&CONFIG.replace(
+        "name = \"rust\"\n",
+        &format!("name = \"rust\"\n{}\n", option),
+    ))
+}
+
+/// Creates then updates the header of a file made only of leading lines,
+/// without a final line feed.
+fn assert_banner_only(option: &str) {
+    let sandbox = sandbox(option);
+    let path = sandbox.file("main.rs", "// banner\n// banner2");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(
+        result.starts_with("// banner\n// banner2\n// File: main.rs\n"),
+        "{}",
+        result
+    );
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+
+    std::fs::write(&path, result.replace("File: main.rs", "File: old.rs")).unwrap();
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    assert_eq!(sandbox.read(&path), result);
+}
+
+#[test]
+fn protected_lines_filling_the_file() {
+    assert_banner_only("protect_leading_lines = 2");
+}
+
+#[test]
+fn insert_after_lines_filling_the_file() {
+    assert_banner_only("insert_after = \"^// banner\"");
+}
+
+#[test]
+fn header_of_an_empty_file_is_not_protected() {
+    let sandbox = sandbox("protect_leading_lines = 1");
+    let path = sandbox.file("main.rs", "");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert_eq!(result.matches("File: ").count(), 1, "{}", result);
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), result);
+}
+
+#[test]
+fn header_within_the_protected_lines_is_found() {
+    let sandbox = sandbox("protect_leading_lines = 2");
+    let path = sandbox.file("main.rs", "fn main() {}");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(result.starts_with("fn main() {}\n// File: "), "{}", result);
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), result);
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use auto_header::Action;
+use common::Sandbox;
+
+#[test]
+fn rst_file_gets_the_built_in_template() {
+    // The shared configuration is strict and has no template for reStructuredText.
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("doc.rst", "Title\n=====\n");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let content = sandbox.read(&path);
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some(".."), "{}", content);
+    assert_eq!(lines.next(), Some("   File: doc.rst"), "{}", content);
+    assert!(content.ends_with("\nTitle\n=====\n"), "{}", content);
+
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), content);
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::{Sandbox, CONFIG};
+use std::{
+    fs,
+    process::{Command, Output},
+};
+
+/// Creates a sandbox holding a read-only file and a writable one, along with
+/// its configuration file. Gets the paths of the configuration and the files.
+fn sandbox() -> (Sandbox, String, String, String) {
+    let sandbox = Sandbox::new();
+    let config = sandbox.file(
+        "auto-header.toml",
+        &format!("{}\n[[project]]\nroot = {:?}\n", CONFIG, sandbox.root),
+    );
+    let locked = sandbox.file("locked.rs", "fn locked() {}\n");
+    let open = sandbox.file("open.rs", "fn open() {}\n");
+    let mut permissions = fs::metadata(&locked).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&locked, permissions).unwrap();
+    (sandbox, config, locked, open)
+}
+
+/// Runs auto-header on the sandbox’s directory.
+fn run(sandbox: &Sandbox, config: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_auto-header"))
+        .args(["--config", config, "--path"])
+        .arg(&sandbox.root)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn read_only_files_are_skipped() {
+    let (sandbox, config, locked, open) = sandbox();
+
+    let output = run(&sandbox, &config, &[]);
+    assert!(output.status.success(), "{:?}", output);
+    let messages = String::from_utf8_lossy(&output.stdout);
+    assert!(messages.contains("locked.rs is read-only"), "{}", messages);
+    assert!(messages.contains("1 skipped, 0 failed"), "{}", messages);
+    assert_eq!(sandbox.read(&locked), "fn locked() {}\n");
+    assert!(sandbox.read(&open).starts_with("// File: open.rs\n"));
+}
+
+#[test]
+fn read_only_files_are_updated_with_chmod() {
+    let (sandbox, config, locked, _) = sandbox();
+
+    let output = run(&sandbox, &config, &["--chmod"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(sandbox.read(&locked).starts_with("// File: locked.rs\n"));
+    assert!(fs::metadata(&locked).unwrap().permissions().readonly());
+}
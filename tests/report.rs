@@ -0,0 +1,147 @@
+mod common;
+
+use auto_header::{
+    report::{on_complete, write_report, ReportEntry, RunSummary},
+    Action,
+};
+use common::{Sandbox, CONFIG};
+use std::{fs, process::Command};
+
+/// Gets entries of every kind of outcome.
+fn entries() -> Vec<ReportEntry> {
+    let outcomes = [
+        ("a.rs", Some(Action::Created)),
+        ("b.rs", Some(Action::Updated)),
+        ("c.rs", Some(Action::UpToDate)),
+        ("d.rs", Some(Action::Recreated)),
+        (
+            "e.rs",
+            Some(Action::Skipped {
+                header_present: false,
+            }),
+        ),
+        ("f.rs", None),
+    ];
+    outcomes
+        .into_iter()
+        .map(|(path, action)| {
+            let mut entry = ReportEntry::new(path);
+            entry.language = Some(String::from("rust"));
+            match action {
+                Some(action) => entry.set_action(action),
+                None => entry.error = Some(String::from("File f.rs is not valid UTF-8.")),
+            }
+            entry
+        })
+        .collect()
+}
+
+#[test]
+fn summary_counts_the_files_by_outcome() {
+    assert_eq!(
+        serde_json::to_string(&RunSummary::new(&entries())).unwrap(),
+        r#"{"created":1,"updated":1,"recreated":1,"replaced":0,"up_to_date":1,"skipped":1,"failed":1}"#
+    );
+}
+
+#[test]
+fn json_report_holds_the_entries() {
+    let sandbox = Sandbox::new();
+    let path = sandbox
+        .root
+        .join("report.json")
+        .to_string_lossy()
+        .into_owned();
+    write_report(&path, &entries()[..2]).unwrap();
+
+    assert_eq!(
+        sandbox.read(&path),
+        r#"[
+  {
+    "path": "a.rs",
+    "language": "rust",
+    "project": null,
+    "action": "created",
+    "error": null
+  },
+  {
+    "path": "b.rs",
+    "language": "rust",
+    "project": null,
+    "action": "updated",
+    "error": null
+  }
+]
+"#
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn completion_command_reads_the_counts() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.root.join("summary.json");
+    on_complete(
+        &format!("cat > '{}' && test $AUTO_HEADER_FAILED = 1", path.display()),
+        &entries(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\"created\":1,\"updated\":1,\"recreated\":1,\"replaced\":0,\"up_to_date\":1,\"skipped\":1,\"failed\":1}\n"
+    );
+}
+
+#[test]
+fn run_reports_every_file() {
+    let sandbox = Sandbox::new();
+    let config = sandbox.file(
+        "auto-header.toml",
+        &format!("{}\n[[project]]\nroot = {:?}\n", CONFIG, sandbox.root),
+    );
+    sandbox.file("a.rs", "fn a() {}\n");
+    fs::write(sandbox.root.join("b.rs"), b"// \xff\xfe\n").unwrap();
+    sandbox.file("notes.txt", "notes\n");
+    let report = sandbox.root.join("report.json");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_auto-header"))
+        .args(["--config", &config, "--path"])
+        .arg(&sandbox.root)
+        .arg("--report-file")
+        .arg(&report)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+
+    let root = sandbox.root.to_string_lossy();
+    assert_eq!(
+        fs::read_to_string(&report).unwrap(),
+        format!(
+            r#"[
+  {{
+    "path": "{root}/a.rs",
+    "language": "rust",
+    "project": "{root}",
+    "action": "created",
+    "error": null
+  }},
+  {{
+    "path": "{root}/b.rs",
+    "language": "rust",
+    "project": "{root}",
+    "action": null,
+    "error": "File {root}/b.rs is not valid UTF-8."
+  }},
+  {{
+    "path": "{root}/notes.txt",
+    "language": "*",
+    "project": "{root}",
+    "action": null,
+    "error": null
+  }}
+]
+"#
+        )
+    );
+}
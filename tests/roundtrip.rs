@@ -0,0 +1,156 @@
+mod common;
+
+use auto_header::{process_file, resolve, Action, Options};
+use common::{Sandbox, CONFIG};
+use proptest::prelude::*;
+
+/// Lines the bodies are made of: code, blank and whitespace lines, non-ASCII
+/// text, lines close to the comment syntaxes of the templates, shebangs
+/// (including one written by a template) and encoding declarations.
+const LINES: [&str; 13] = [
+    "fn main() {}",
+    "",
+    "    let x = 1;",
+    "\t",
+    "é ü 中文 🦀",
+    "#[test]",
+    "/* comment */",
+    "-- not a header",
+    "\u{feff}bom",
+    "x",
+    "#!/bin/sh",
+    "#!/usr/bin/env python3",
+    "# -*- coding: utf-8 -*-",
+];
+
+/// Options of the default template handling the leading lines of the files.
+const PRELUDES: [&str; 4] = [
+    "",
+    "protect_leading_lines = 1",
+    "protect_leading_lines = 2",
+    "insert_after = '^(#\\[test\\]|x)$'",
+];
+
+/// Names of the files, covering the templates with `before` lines and block
+/// comments.
+const NAMES: [&str; 5] = ["main.rs", "script.py", "script.sh", "init.lua", "main.c"];
+
+/// Generates the content of a file: lines separated by LF or CRLF, with or
+/// without a final line feed.
+fn content() -> impl Strategy<Value = String> {
+    (
+        prop::collection::vec((prop::sample::select(&LINES[..]), any::<bool>()), 0..8),
+        any::<bool>(),
+    )
+        .prop_map(|(lines, final_newline)| {
+            let mut content = String::new();
+            for (index, (line, crlf)) in lines.iter().enumerate() {
+                content.push_str(line);
+                if index + 1 < lines.len() || final_newline {
+                    content.push_str(if *crlf { "\r\n" } else { "\n" });
+                }
+            }
+            content
+        })
+}
+
+/// Options of a generated template.
+#[derive(Debug)]
+struct TemplateOptions {
+    /// Number of blank lines ending the header.
+    blank_lines_after: usize,
+    /// Option handling the leading lines of the files (one of `PRELUDES`).
+    prelude: &'static str,
+    /// Whether the shebang of the files stays first.
+    keep_shebang: bool,
+}
+
+/// Generates the options of the default template.
+fn template_options() -> impl Strategy<Value = TemplateOptions> {
+    (
+        0..3usize,
+        prop::sample::select(&PRELUDES[..]),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(blank_lines_after, prelude, keep_shebang)| TemplateOptions {
+                blank_lines_after,
+                prelude,
+                keep_shebang,
+            },
+        )
+}
+
+/// Creates a sandbox whose default template has the given options.
+fn sandbox(options: &TemplateOptions) -> Sandbox {
+    let after = vec!["\"\""; options.blank_lines_after].join(", ");
+    Sandbox::with_config(&CONFIG.replace(
+        "before = []\nafter = [\"\"]",
+        &format!(
+            "before = []\nafter = [{}]\nkeep_shebang = {}\n{}",
+            after, options.keep_shebang, options.prelude
+        ),
+    ))
+}
+
+/// Checks whether the lines of some content are all found, in order, in
+/// another one, their trailing whitespace ignored (a blank line kept before
+/// the header is rewritten with the header).
+fn is_subsequence(content: &str, result: &str) -> bool {
+    let mut lines = result.split('\n').map(str::trim_end);
+    content
+        .split('\n')
+        .all(|line| lines.any(|other| other == line.trim_end()))
+}
+
+/// Processes a file, without unwrapping the result.
+fn process(sandbox: &Sandbox, path: &str) -> auto_header::error::Result<Action> {
+    let (project, template) = resolve(&sandbox.config, path)?;
+    let options = Options {
+        create: true,
+        update: true,
+        ..Default::default()
+    };
+    process_file(path, &project, &template, &options)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn headers_round_trip(
+        content in content(),
+        name in prop::sample::select(&NAMES[..]),
+        options in template_options(),
+    ) {
+        let sandbox = sandbox(&options);
+        let path = sandbox.file(name, &content);
+
+        prop_assert_eq!(process(&sandbox, &path)?, Action::Created);
+        let first = sandbox.read(&path);
+        // Without leading lines to keep, the header is added before the content.
+        if options.prelude.is_empty() && !content.starts_with("#!") {
+            prop_assert!(first.ends_with(&content), "body was modified:\n{:?}", first);
+        }
+        prop_assert!(is_subsequence(&content, &first), "lines were lost:\n{:?}", first);
+
+        for _ in 0..2 {
+            prop_assert_eq!(process(&sandbox, &path)?, Action::UpToDate);
+            prop_assert_eq!(sandbox.read(&path), first.clone());
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_is_left_untouched(
+        bytes in prop::collection::vec(any::<u8>(), 1..64),
+        name in prop::sample::select(&NAMES[..]),
+    ) {
+        prop_assume!(std::str::from_utf8(&bytes).is_err());
+        let sandbox = Sandbox::new();
+        let path = sandbox.root.join(name).to_string_lossy().into_owned();
+        std::fs::write(&path, &bytes).unwrap();
+
+        prop_assert!(process(&sandbox, &path).is_err());
+        prop_assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    }
+}
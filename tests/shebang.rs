@@ -0,0 +1,126 @@
+mod common;
+
+use auto_header::{header::write_header, Action};
+use common::{Sandbox, CONFIG};
+
+/// Creates a sandbox whose python template doesn’t write a shebang.
+fn sandbox() -> Sandbox {
+    Sandbox::with_config(&CONFIG.replace(
+        "name = \"python\"\nbefore = [\"#!/usr/bin/env python3\", \"\"]",
+        "name = \"python\"",
+    ))
+}
+
+#[test]
+fn header_is_created_after_the_shebang() {
+    let sandbox = sandbox();
+    let path = sandbox.file("script.py", "#!/usr/bin/python3\nprint()\n");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(
+        result.starts_with("#!/usr/bin/python3\n# File: "),
+        "{}",
+        result
+    );
+    assert!(result.ends_with("reserved\n\nprint()\n"), "{}", result);
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), result);
+}
+
+#[test]
+fn encoding_declaration_stays_after_the_shebang() {
+    let sandbox = sandbox();
+    let path = sandbox.file(
+        "script.py",
+        "#!/usr/bin/python3\n# -*- coding: utf-8 -*-\nprint()\n",
+    );
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(
+        result.starts_with("#!/usr/bin/python3\n# -*- coding: utf-8 -*-\n# File: "),
+        "{}",
+        result
+    );
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+}
+
+#[test]
+fn shebang_written_by_the_template_is_not_kept_twice() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("script.py", "#!/usr/bin/env python3\nprint()\n");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert_eq!(result.matches("#!").count(), 1, "{}", result);
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+}
+
+#[test]
+fn own_shebang_replaces_the_template_one() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("script.py", "#!/usr/bin/python2\nprint 1\n");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(
+        result.starts_with("#!/usr/bin/python2\n\n# File: "),
+        "{}",
+        result
+    );
+    assert_eq!(result.matches("#!").count(), 1, "{}", result);
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), result);
+}
+
+#[test]
+fn shebang_can_be_moved_below_the_header() {
+    let sandbox = Sandbox::with_config(
+        &CONFIG
+            .replace(
+                "name = \"python\"\nbefore = [\"#!/usr/bin/env python3\", \"\"]",
+                "name = \"python\"",
+            )
+            .replace("before = []\n", "before = []\nkeep_shebang = false\n"),
+    );
+    let path = sandbox.file("script.py", "#!/usr/bin/python3\nprint()\n");
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(result.starts_with("# File: "), "{}", result);
+}
+
+#[test]
+fn header_is_written_after_the_shebang() {
+    let sandbox = sandbox();
+    let path = sandbox.file("script.py", "#!/bin/sh\necho\n");
+    let header = vec![String::from("# header"), String::new()];
+
+    write_header(&path, &header).unwrap();
+    assert_eq!(sandbox.read(&path), "#!/bin/sh\n# header\n\necho\n");
+}
+
+/// Creates then updates the header of a file holding only a shebang, without
+/// a final line feed.
+fn assert_shebang_only(sandbox: &Sandbox, name: &str, shebang: &str) {
+    let path = sandbox.file(name, shebang);
+
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(result.starts_with(&format!("{}\n", shebang)), "{}", result);
+    assert!(result.contains(&format!("File: {}", name)), "{}", result);
+    assert_eq!(result.matches("#!").count(), 1, "{}", result);
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+
+    let outdated = result.replace(&format!("File: {}", name), "File: old");
+    std::fs::write(&path, &outdated).unwrap();
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    assert_eq!(sandbox.read(&path), result);
+}
+
+#[test]
+fn shebang_only_file_without_line_feed() {
+    assert_shebang_only(&sandbox(), "b.py", "#!/usr/bin/python3");
+    assert_shebang_only(&Sandbox::new(), "a.sh", "#!/bin/sh");
+}
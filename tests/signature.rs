@@ -0,0 +1,125 @@
+mod common;
+
+use auto_header::{error::AutoHeaderError, process_file, resolve, Action, Options};
+use common::{Sandbox, CONFIG};
+use std::fs;
+
+/// Creates a sandbox whose templates sign their header.
+fn sandbox() -> Sandbox {
+    Sandbox::with_config(&CONFIG.replace(
+        "#copyright_notice\"\"\"",
+        "#copyright_notice\nSignature: #signature\"\"\"",
+    ))
+}
+
+/// Computes the FNV-1a hash of the lines of a header, but its signature line.
+fn fnv1a(header: &[&str], skip: usize) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (_, line) in header
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != skip)
+    {
+        for byte in line.trim_end().bytes().chain(*b"\n") {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Gets the lines of the header of a file (ending with its blank line), and
+/// the index of its signature line.
+fn header_of(content: &str) -> (Vec<&str>, usize) {
+    let lines: Vec<&str> = content.split('\n').collect();
+    let end = lines.iter().position(|line| line.is_empty()).unwrap();
+    let index = lines
+        .iter()
+        .position(|line| line.starts_with("// Signature: "))
+        .unwrap();
+    (lines[..=end].to_vec(), index)
+}
+
+/// Processes a file, forcing the overwrite of edited headers or not.
+fn process(sandbox: &Sandbox, path: &str, force: bool) -> Result<Action, AutoHeaderError> {
+    let (project, template) = resolve(&sandbox.config, path).unwrap();
+    let options = Options {
+        create: true,
+        update: true,
+        force,
+        ..Default::default()
+    };
+    process_file(path, &project, &template, &options)
+}
+
+#[test]
+fn signature_is_the_hash_of_the_header() {
+    let sandbox = sandbox();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+
+    let content = sandbox.read(&path);
+    let (header, index) = header_of(&content);
+    assert_eq!(
+        header[index],
+        format!("// Signature: {}", fnv1a(&header, index))
+    );
+}
+
+#[test]
+fn hand_edited_header_is_detected() {
+    let sandbox = sandbox();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let signed = sandbox.read(&path);
+    let edited = signed.replace("// Modified by: Jane Doe", "// Modified by: John Smith");
+    sandbox.file("main.rs", &edited);
+
+    assert!(matches!(
+        process(&sandbox, &path, false),
+        Err(AutoHeaderError::EditedHeader(_))
+    ));
+    assert_eq!(sandbox.read(&path), edited);
+}
+
+#[test]
+fn stale_signature_is_detected() {
+    let sandbox = sandbox();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+    assert_eq!(sandbox.process(&path), Action::Created);
+    let signed = sandbox.read(&path);
+    let (header, index) = header_of(&signed);
+    let stale = signed.replace(header[index], "// Signature: 0123456789abcdef");
+    sandbox.file("main.rs", &stale);
+
+    assert!(matches!(
+        process(&sandbox, &path, false),
+        Err(AutoHeaderError::EditedHeader(_))
+    ));
+    assert_eq!(process(&sandbox, &path, true).unwrap(), Action::Updated);
+    let content = sandbox.read(&path);
+    let (header, index) = header_of(&content);
+    assert_eq!(
+        header[index],
+        format!("// Signature: {}", fnv1a(&header, index))
+    );
+}
+
+#[test]
+fn regenerated_header_round_trips_its_signature() {
+    let sandbox = sandbox();
+    let old = sandbox.file("old.rs", "fn main() {}\n");
+    assert_eq!(sandbox.process(&old), Action::Created);
+    let path = sandbox.root.join("main.rs").to_string_lossy().into_owned();
+    fs::rename(&old, &path).unwrap();
+
+    assert_eq!(sandbox.process(&path), Action::Updated);
+    let content = sandbox.read(&path);
+    let (header, index) = header_of(&content);
+    assert_eq!(
+        header[index],
+        format!("// Signature: {}", fnv1a(&header, index))
+    );
+    assert_eq!(sandbox.process(&path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), content);
+}
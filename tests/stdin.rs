@@ -0,0 +1,68 @@
+mod common;
+
+use common::{Sandbox, CONFIG};
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Runs auto-header in the sandbox on content given on its standard input.
+fn run_stdin(sandbox: &Sandbox, args: &[&str], input: &str) -> Output {
+    let config = sandbox.file(
+        "auto-header.toml",
+        &format!("{}\n[[project]]\nroot = {:?}\n", CONFIG, sandbox.root),
+    );
+    let mut child = Command::new(env!("CARGO_BIN_EXE_auto-header"))
+        .current_dir(&sandbox.root)
+        .args([
+            "--config",
+            &config,
+            "--timestamp-source",
+            "2001-02-03T04:05:06Z",
+        ])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn dash_path_round_trips_through_the_standard_streams() {
+    let sandbox = Sandbox::new();
+    let args = ["--path", "-", "--stdin-filename", "src/main.rs"];
+
+    let output = run_stdin(&sandbox, &args, "fn main() {}\n");
+    assert!(output.status.success());
+    let created = String::from_utf8(output.stdout).unwrap();
+    assert!(created.starts_with("// File: src/main.rs\n"), "{}", created);
+    assert!(created.ends_with("\nfn main() {}\n"), "{}", created);
+    assert!(!sandbox.root.join("src/main.rs").exists());
+
+    let output = run_stdin(&sandbox, &args, &created);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), created);
+}
+
+#[test]
+fn standard_input_outside_of_a_project_is_an_error() {
+    let sandbox = Sandbox::new();
+    let elsewhere = std::env::temp_dir().join("elsewhere.rs");
+    let elsewhere = elsewhere.to_string_lossy();
+
+    let output = run_stdin(
+        &sandbox,
+        &["--path", "-", "--stdin-filename", &elsewhere],
+        "fn main() {}\n",
+    );
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use auto_header::{process_file, resolve, Action, Options};
+use chrono::{DateTime, Local};
+use common::{Sandbox, CONFIG};
+use std::{fs, process::Command, time::SystemTime};
+
+/// Processes a file with a fixed timestamp from 2001.
+fn process(sandbox: &Sandbox, path: &str) -> Action {
+    let (project, template) = resolve(&sandbox.config, path).unwrap();
+    let timestamp = DateTime::parse_from_rfc3339("2001-02-03T12:00:00+00:00").unwrap();
+    let options = Options {
+        create: true,
+        update: true,
+        timestamp: Some(timestamp.with_timezone(&Local)),
+        ..Default::default()
+    };
+    process_file(path, &project, &template, &options).unwrap()
+}
+
+/// Gets the number of seconds since the last modification of a file.
+fn age(path: &str) -> u64 {
+    let modified = fs::metadata(path).unwrap().modified().unwrap();
+    SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[test]
+fn timestamp_is_only_written_in_the_header() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("main.rs", "fn main() {}\n");
+
+    assert_eq!(process(&sandbox, &path), Action::Created);
+    let result = sandbox.read(&path);
+    assert!(
+        result.contains("Last modified: Saturday 03 February 2001"),
+        "{}",
+        result
+    );
+    assert!(age(&path) < 60);
+
+    assert_eq!(process(&sandbox, &path), Action::UpToDate);
+    assert_eq!(sandbox.read(&path), result);
+}
+
+#[test]
+fn source_date_epoch_is_only_used_when_asked() {
+    let sandbox = Sandbox::new();
+    let config = sandbox.file(
+        "auto-header.toml",
+        &format!("{}\n[[project]]\nroot = {:?}\n", CONFIG, sandbox.root),
+    );
+    let run = |name: &str, args: &[&str]| {
+        let path = sandbox.file(name, "fn main() {}\n");
+        let status = Command::new(env!("CARGO_BIN_EXE_auto-header"))
+            .args(["--config", &config, "--path", &path])
+            .args(args)
+            .env("SOURCE_DATE_EPOCH", "43200")
+            .status()
+            .unwrap();
+        assert!(status.success());
+        sandbox.read(&path)
+    };
+
+    assert!(!run("implicit.rs", &[]).contains("1970"));
+    let explicit = run("explicit.rs", &["--timestamp-source", "env"]);
+    assert!(
+        explicit.contains("Last modified: Thursday 01 January 1970"),
+        "{}",
+        explicit
+    );
+}